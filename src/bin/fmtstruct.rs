@@ -0,0 +1,298 @@
+/* src/bin/fmtstruct.rs */
+
+use clap::{Parser, Subcommand};
+use fmtstruct::format::{AnyFormat, Style};
+use fmtstruct::{Format, TomlEditor, Value};
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+type Result<T> = core::result::Result<T, Box<dyn Error>>;
+
+/// Converts and canonically formats configuration files.
+///
+/// Files are read with the same parsers as `DynLoader`. The canonical form of
+/// a document is its pretty-printed serialization, with keys sorted. TOML is
+/// formatted in place, keeping its comments; `fmt` refuses to rewrite a file
+/// if that would change its data or drop YAML comments. Formats that are not
+/// self-describing, such as Postcard, cannot be read without a type and are
+/// rejected.
+#[derive(Parser)]
+#[command(name = "fmtstruct", version)]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Converts a file to the format of the output file.
+	Convert {
+		/// Input file, or `-` for stdin.
+		input: PathBuf,
+		/// Output file, or `-` for stdout.
+		output: PathBuf,
+		/// Input format, instead of detecting it from the extension.
+		#[arg(long, value_parser = parse_format)]
+		from: Option<AnyFormat>,
+		/// Output format, instead of detecting it from the extension.
+		#[arg(long, value_parser = parse_format)]
+		to: Option<AnyFormat>,
+		/// Writes compact instead of pretty output.
+		#[arg(long)]
+		compact: bool,
+	},
+	/// Rewrites files in canonical form.
+	Fmt {
+		/// Files to format, or `-` to format stdin to stdout.
+		#[arg(required = true)]
+		files: Vec<PathBuf>,
+		/// Format of the files, instead of detecting it from the extension.
+		#[arg(long, value_parser = parse_format)]
+		format: Option<AnyFormat>,
+		/// Writes nothing and exits with 1 if a file is not canonical.
+		#[arg(long)]
+		check: bool,
+	},
+}
+
+fn main() -> ExitCode {
+	let result = match Cli::parse().command {
+		Command::Convert {
+			input,
+			output,
+			from,
+			to,
+			compact,
+		} => convert(&input, &output, from, to, compact),
+		Command::Fmt {
+			files,
+			format,
+			check,
+		} => fmt(&files, format, check),
+	};
+	match result {
+		Ok(code) => code,
+		Err(e) => {
+			eprintln!("fmtstruct: {}", e);
+			ExitCode::from(2)
+		}
+	}
+}
+
+fn convert(
+	input: &Path,
+	output: &Path,
+	from: Option<AnyFormat>,
+	to: Option<AnyFormat>,
+	compact: bool,
+) -> Result<ExitCode> {
+	let from = untyped(input, detect(input, from, "--from")?)?;
+	let to = untyped(output, detect(output, to, "--to")?)?;
+	let value = from
+		.parse_value(&read(input)?)
+		.map_err(|e| context(input, e))?;
+	let style = if compact {
+		Style::Compact
+	} else {
+		Style::Pretty
+	};
+	write(
+		output,
+		&render(to, value, style).map_err(|e| context(input, e))?,
+	)?;
+	Ok(ExitCode::SUCCESS)
+}
+
+fn fmt(files: &[PathBuf], format: Option<AnyFormat>, check: bool) -> Result<ExitCode> {
+	let mut unformatted = false;
+	for path in files {
+		let format = untyped(path, detect(path, format, "--format")?)?;
+		let input = read(path)?;
+		let output = canonical(format, &input).map_err(|e| context(path, e))?;
+
+		if is_stdio(path) {
+			if check {
+				unformatted |= output != input;
+			} else {
+				write(path, &output)?;
+			}
+		} else if output != input {
+			if check {
+				println!("{}", path.display());
+				unformatted = true;
+			} else {
+				write(path, &output)?;
+			}
+		}
+	}
+	Ok(if unformatted {
+		ExitCode::FAILURE
+	} else {
+		ExitCode::SUCCESS
+	})
+}
+
+/// Returns the canonical form of `input`, failing if it would not hold the
+/// same data.
+fn canonical(format: AnyFormat, input: &[u8]) -> Result<Vec<u8>> {
+	let value = format.parse_value(input)?;
+	let output = match format {
+		AnyFormat::Toml => {
+			let mut editor = TomlEditor::new("-", input)?;
+			editor.format();
+			editor.to_bytes()
+		}
+		AnyFormat::Yaml if has_yaml_comment(input) => {
+			return Err("formatting would drop its comments".into());
+		}
+		_ => render(format, value.clone(), Style::Pretty)?,
+	};
+	if format.parse_value(&output)? != value {
+		return Err("formatting would change its data".into());
+	}
+	Ok(output)
+}
+
+/// Serializes `value`, ending text output with a newline.
+///
+/// TOML is written through `TomlEditor`, which keeps datetimes.
+fn render(format: AnyFormat, value: Value, style: Style) -> Result<Vec<u8>> {
+	let mut bytes = match format {
+		AnyFormat::Toml => TomlEditor::from_value("-", value)?.to_bytes(),
+		format => format.serialize(&value, style)?,
+	};
+	if format.self_describing() && !bytes.ends_with(b"\n") {
+		bytes.push(b'\n');
+	}
+	Ok(bytes)
+}
+
+/// Whether YAML input has a comment: a `#` at the start of a line or after
+/// whitespace, outside quoted and block scalars.
+fn has_yaml_comment(input: &[u8]) -> bool {
+	let text = String::from_utf8_lossy(input);
+	// The open quote of a scalar, which may span lines.
+	let mut quote = None;
+	// The indentation of the line that opened a block scalar.
+	let mut block = None;
+	for line in text.lines() {
+		let indent = line.len() - line.trim_start().len();
+		if let Some(parent) = block {
+			if line.trim().is_empty() || indent > parent {
+				continue;
+			}
+			block = None;
+		}
+		let mut prev = ' ';
+		let mut last = None;
+		let mut chars = line.chars().peekable();
+		while let Some(c) = chars.next() {
+			match quote {
+				// `''` is an escaped quote
+				Some('\'') if c == '\'' && chars.next_if_eq(&'\'').is_none() => quote = None,
+				Some('"') if c == '\\' => {
+					chars.next();
+				}
+				Some('"') if c == '"' => quote = None,
+				Some(_) => {}
+				None if c == '#' && prev.is_whitespace() => return true,
+				// A quote only starts a scalar where a value or key may begin
+				None
+					if matches!(c, '\'' | '"')
+						&& last.is_none_or(|last| matches!(last, ':' | '-' | '?' | ',' | '[' | '{')) =>
+				{
+					quote = Some(c);
+				}
+				None => {}
+			}
+			prev = c;
+			if !c.is_whitespace() {
+				last = Some(c);
+			}
+		}
+		if quote.is_none() && opens_block(line) {
+			block = Some(indent);
+		}
+	}
+	false
+}
+
+/// Whether a YAML line ends with a block scalar header such as `|` or `>-`.
+fn opens_block(line: &str) -> bool {
+	let header = line
+		.trim_end()
+		.trim_end_matches(|c: char| c == '+' || c == '-' || c.is_ascii_digit());
+	let Some(rest) = header
+		.strip_suffix('|')
+		.or_else(|| header.strip_suffix('>'))
+	else {
+		return false;
+	};
+	rest.is_empty() || rest.ends_with(char::is_whitespace)
+}
+
+/// Fails for formats that cannot be read without a target type.
+fn untyped(path: &Path, format: AnyFormat) -> Result<AnyFormat> {
+	if format.self_describing() {
+		return Ok(format);
+	}
+	Err(
+		format!(
+			"{}: {} is not self-describing and cannot be converted or formatted without a type",
+			path.display(),
+			format.extensions().first().copied().unwrap_or("the format")
+		)
+		.into(),
+	)
+}
+
+fn detect(path: &Path, explicit: Option<AnyFormat>, flag: &str) -> Result<AnyFormat> {
+	if let Some(format) = explicit {
+		return Ok(format);
+	}
+	path
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.and_then(AnyFormat::from_extension)
+		.ok_or_else(|| {
+			format!(
+				"cannot detect the format of `{}`, pass {}",
+				path.display(),
+				flag
+			)
+			.into()
+		})
+}
+
+fn parse_format(name: &str) -> core::result::Result<AnyFormat, String> {
+	AnyFormat::from_extension(name).ok_or_else(|| format!("unknown format `{}`", name))
+}
+
+fn is_stdio(path: &Path) -> bool {
+	path.as_os_str() == "-"
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+	let mut bytes = Vec::new();
+	if is_stdio(path) {
+		std::io::stdin().read_to_end(&mut bytes)?;
+	} else {
+		bytes = std::fs::read(path).map_err(|e| context(path, e))?;
+	}
+	Ok(bytes)
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<()> {
+	if is_stdio(path) {
+		std::io::stdout().write_all(bytes)?;
+	} else {
+		std::fs::write(path, bytes).map_err(|e| context(path, e))?;
+	}
+	Ok(())
+}
+
+fn context(path: &Path, e: impl std::fmt::Display) -> Box<dyn Error> {
+	format!("{}: {}", path.display(), e).into()
+}
@@ -0,0 +1,235 @@
+/* src/diagnostics.rs */
+
+use crate::{FmtError, UnknownKey};
+use miette::{Diagnostic, LabeledSpan, NamedSource, Report, SourceCode};
+use std::fmt;
+use std::ops::Range;
+
+/// An `FmtError` presented as a `miette` diagnostic, with an error code, help
+/// text and, for parse errors and unknown keys, labeled spans over the
+/// original input.
+///
+/// `FmtError` renders its own caret snippet; this wrapper displays a short
+/// headline instead and leaves the snippet to the report handler. Errors
+/// that only know a line and column, such as type errors in a JSON file
+/// merged with others, keep the location in the headline. Unknown keys are
+/// labeled where the format reports key positions (TOML), in the file of the
+/// first located key. Errors convert into `miette::Report` directly, so `?`
+/// works in a function returning `miette::Result`:
+///
+/// ```text
+/// fmtstruct::parse
+///
+///   × parse error at `server.port`
+///    ╭─[config.toml:3:8]
+///  2 │ [server]
+///  3 │ port = "80"
+///    ·        ──┬─
+///    ·          ╰── invalid type: string "80", expected u16
+///    ╰────
+///   help: check `server.port` against the target type
+/// ```
+#[derive(Debug)]
+pub struct FmtDiagnostic {
+	error: FmtError,
+	source: Option<NamedSource<String>>,
+}
+
+impl FmtDiagnostic {
+	pub fn new(error: FmtError) -> Self {
+		let source = match &error {
+			FmtError::ParseError(e) if e.span.is_some() => e.source.as_ref().map(|source| {
+				let name = e.key.as_deref().unwrap_or("<input>");
+				let named = NamedSource::new(name, source.clone());
+				match e.format {
+					Some(format) => named.with_language(format),
+					None => named,
+				}
+			}),
+			FmtError::UnknownKeys(keys) => labeled_file(keys).and_then(|key| {
+				let source = key.source.clone()?;
+				Some(NamedSource::new(key.file.as_deref()?, source))
+			}),
+			_ => None,
+		};
+		Self { error, source }
+	}
+
+	pub fn error(&self) -> &FmtError {
+		&self.error
+	}
+
+	pub fn into_inner(self) -> FmtError {
+		self.error
+	}
+}
+
+impl From<FmtError> for FmtDiagnostic {
+	fn from(error: FmtError) -> Self {
+		Self::new(error)
+	}
+}
+
+impl From<FmtError> for Report {
+	fn from(error: FmtError) -> Self {
+		Report::new(FmtDiagnostic::new(error))
+	}
+}
+
+impl fmt::Display for FmtDiagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.error {
+			// The message moves into the label when the span can be shown
+			FmtError::ParseError(e) if self.source.is_some() => {
+				write!(f, "parse error")?;
+				if let Some(path) = &e.path {
+					write!(f, " at `{}`", path)?;
+				}
+				Ok(())
+			}
+			#[cfg(feature = "jsonschema")]
+			FmtError::Schema(_) => write!(f, "schema validation failed"),
+			#[cfg(feature = "validate")]
+			FmtError::Validation(_) => write!(f, "validation failed"),
+			error => write!(f, "{}", error),
+		}
+	}
+}
+
+impl std::error::Error for FmtDiagnostic {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		std::error::Error::source(&self.error)
+	}
+}
+
+impl Diagnostic for FmtDiagnostic {
+	fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+		let code = match &self.error {
+			FmtError::ParseError(_) => "fmtstruct::parse",
+			FmtError::SerializeError(_) => "fmtstruct::serialize",
+			FmtError::InvalidPath(_) => "fmtstruct::invalid_path",
+			FmtError::PathNotFound(_) => "fmtstruct::path_not_found",
+			FmtError::IncludeCycle(_) => "fmtstruct::include_cycle",
+			FmtError::IncludeDepth(_) => "fmtstruct::include_depth",
+			FmtError::UnknownKeys(_) => "fmtstruct::unknown_keys",
+			FmtError::Multiple(_) => "fmtstruct::multiple",
+			FmtError::Interpolation { .. } => "fmtstruct::interpolation",
+			FmtError::NotFound => "fmtstruct::not_found",
+			FmtError::Custom(_) => "fmtstruct::custom",
+			FmtError::PreProcess(_) => "fmtstruct::pre_process",
+			FmtError::Io(_) => "fmtstruct::io",
+			#[cfg(feature = "fs")]
+			FmtError::SandboxViolation => "fmtstruct::sandbox",
+			#[cfg(feature = "jsonschema")]
+			FmtError::Schema(_) => "fmtstruct::schema",
+			#[cfg(feature = "validate")]
+			FmtError::Validation(_) => "fmtstruct::validation",
+		};
+		Some(Box::new(code))
+	}
+
+	fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+		let help = match &self.error {
+			FmtError::ParseError(e) => match (&e.path, e.format) {
+				(Some(path), _) => format!("check `{}` against the target type", path),
+				(None, Some(format)) => format!("the input is not valid {}", format),
+				(None, None) => return None,
+			},
+			FmtError::InvalidPath(_) => {
+				String::from("paths are dotted keys with optional indices, e.g. `servers[0].port`")
+			}
+			FmtError::IncludeCycle(_) => {
+				String::from("remove one of the include directives in the chain")
+			}
+			FmtError::IncludeDepth(_) => {
+				String::from("flatten the includes or raise the loader's `max_include_depth`")
+			}
+			FmtError::UnknownKeys(keys) => {
+				let suggestions: Vec<_> = keys
+					.iter()
+					.filter_map(|key| {
+						let suggestion = key.suggestion?;
+						Some(format!(
+							"did you mean `{}` instead of `{}`?",
+							suggestion, key.path
+						))
+					})
+					.collect();
+				if suggestions.is_empty() {
+					String::from("remove the keys or add matching fields to the target type")
+				} else {
+					suggestions.join("\n")
+				}
+			}
+			FmtError::Interpolation { .. } => {
+				String::from("define the variable, or give a default with `${VAR:-default}`")
+			}
+			#[cfg(feature = "fs")]
+			FmtError::SandboxViolation => String::from(
+				"keys must resolve inside the source root; `..`, absolute paths and \
+				 symlinks leading out of it are rejected",
+			),
+			#[cfg(feature = "jsonschema")]
+			FmtError::Schema(violations) => violations
+				.iter()
+				.map(ToString::to_string)
+				.collect::<Vec<_>>()
+				.join("\n"),
+			#[cfg(feature = "validate")]
+			FmtError::Validation(errors) => crate::FmtErrors::from(FmtError::Validation(errors.clone()))
+				.iter()
+				.map(ToString::to_string)
+				.collect::<Vec<_>>()
+				.join("\n"),
+			_ => return None,
+		};
+		Some(Box::new(help))
+	}
+
+	fn source_code(&self) -> Option<&dyn SourceCode> {
+		self.source.as_ref().map(|source| source as &dyn SourceCode)
+	}
+
+	fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+		self.source.as_ref()?;
+		match &self.error {
+			FmtError::ParseError(e) => {
+				let (Some(span), Some(source)) = (&e.span, &e.source) else {
+					return None;
+				};
+				let label = label(Some(e.message.clone()), span, source);
+				Some(Box::new(core::iter::once(label)))
+			}
+			FmtError::UnknownKeys(keys) => {
+				let file = labeled_file(keys)?.file.as_ref();
+				let labels = keys
+					.iter()
+					.filter(move |key| key.file.as_ref() == file)
+					.filter_map(|key| {
+						let (span, source) = (key.span.as_ref()?, key.source.as_ref()?);
+						let message = match key.suggestion {
+							Some(suggestion) => format!("did you mean `{}`?", suggestion),
+							None => String::from("unknown key"),
+						};
+						Some(label(Some(message), span, source))
+					});
+				Some(Box::new(labels))
+			}
+			_ => None,
+		}
+	}
+}
+
+/// The first unknown key that can be shown in its file.
+fn labeled_file(keys: &[UnknownKey]) -> Option<&UnknownKey> {
+	keys
+		.iter()
+		.find(|key| key.file.is_some() && key.span.is_some() && key.source.is_some())
+}
+
+/// Labels `span`, clamped to `source`.
+fn label(message: Option<String>, span: &Range<usize>, source: &str) -> LabeledSpan {
+	let start = span.start.min(source.len());
+	let end = span.end.clamp(start, source.len());
+	LabeledSpan::new(message, start, end - start)
+}
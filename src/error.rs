@@ -1,22 +1,76 @@
 /* src/error.rs */
 
-#[cfg(not(feature = "std"))]
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(any(feature = "alloc", not(feature = "std")))]
 use core::fmt;
+#[cfg(feature = "alloc")]
+use core::ops::Range;
 
 /// Core error type for the fmtstruct crate.
 #[derive(Debug)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum FmtError {
-	/// Parsing error from format implementation.
+	/// Parsing error from format implementation, with its location if known.
 	#[cfg(feature = "alloc")]
-	#[cfg_attr(feature = "std", error("parse error: {0}"))]
-	ParseError(alloc::string::String),
+	#[cfg_attr(feature = "std", error("{0}"))]
+	ParseError(alloc::boxed::Box<ParseError>),
 
 	/// Parsing error from format implementation.
 	#[cfg(not(feature = "alloc"))]
 	#[cfg_attr(feature = "std", error("parse error"))]
 	ParseError,
 
+	/// Serialization error from format implementation.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("serialize error: {0}"))]
+	SerializeError(alloc::string::String),
+
+	/// Malformed dotted key path.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("invalid path: {0}"))]
+	InvalidPath(alloc::string::String),
+
+	/// Dotted key path does not exist in the document.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("path not found: {0}"))]
+	PathNotFound(alloc::string::String),
+
+	/// Include directives form a cycle; holds the chain of keys.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("include cycle: {0}"))]
+	IncludeCycle(alloc::string::String),
+
+	/// Include directives are nested deeper than the configured maximum.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("include depth exceeds {0}"))]
+	IncludeDepth(usize),
+
+	/// The document has keys that match no field of the target type; only
+	/// reported by loaders that deny unknown keys.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(
+		feature = "std",
+		error("unknown keys: {}", crate::value::unknown_summary(.0))
+	)]
+	UnknownKeys(alloc::vec::Vec<crate::value::UnknownKey>),
+
+	/// Every problem found by a loader in accumulate mode.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("{0}"))]
+	Multiple(FmtErrors),
+
+	/// A `${...}` placeholder could not be expanded.
+	#[cfg(feature = "std")]
+	#[error("interpolation failed at `{key}` in {file}: {message}")]
+	Interpolation {
+		file: String,
+		key: String,
+		message: String,
+	},
+
 	/// Resource not found.
 	#[cfg_attr(feature = "std", error("not found"))]
 	NotFound,
@@ -25,6 +79,11 @@ pub enum FmtError {
 	#[cfg_attr(feature = "std", error("custom error: {0}"))]
 	Custom(&'static str),
 
+	/// A pre-processing hook rejected the loaded value.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(feature = "std", error("pre-processing failed: {0}"))]
+	PreProcess(alloc::string::String),
+
 	/// IO error from source, only available in std environment.
 	#[cfg(feature = "std")]
 	#[error("io error: {0}")]
@@ -35,26 +94,537 @@ pub enum FmtError {
 	#[error("sandbox violation")]
 	SandboxViolation,
 
+	/// The document violates the loader's JSON Schema.
+	#[cfg(feature = "jsonschema")]
+	#[error("schema validation failed: {}", crate::loader::schema_summary(.0))]
+	Schema(Vec<crate::SchemaViolation>),
+
 	/// Validation error from validator crate.
 	#[cfg(feature = "validate")]
 	#[cfg_attr(feature = "std", error("validation failed: {0}"))]
 	Validation(#[cfg(feature = "std")] validator::ValidationErrors),
 }
 
+#[cfg(not(feature = "std"))]
+impl core::error::Error for FmtError {}
+
 #[cfg(not(feature = "std"))]
 impl fmt::Display for FmtError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
 			#[cfg(feature = "alloc")]
-			Self::ParseError(msg) => write!(f, "Parse error: {}", msg),
+			Self::ParseError(e) => write!(f, "{}", e),
 			#[cfg(not(feature = "alloc"))]
 			Self::ParseError => write!(f, "Parse error"),
+			#[cfg(feature = "alloc")]
+			Self::SerializeError(msg) => write!(f, "Serialize error: {}", msg),
+			#[cfg(feature = "alloc")]
+			Self::InvalidPath(path) => write!(f, "Invalid path: {}", path),
+			#[cfg(feature = "alloc")]
+			Self::PathNotFound(path) => write!(f, "Path not found: {}", path),
+			#[cfg(feature = "alloc")]
+			Self::IncludeCycle(chain) => write!(f, "Include cycle: {}", chain),
+			#[cfg(feature = "alloc")]
+			Self::IncludeDepth(depth) => write!(f, "Include depth exceeds {}", depth),
+			#[cfg(feature = "alloc")]
+			Self::Multiple(errors) => write!(f, "{}", errors),
+			#[cfg(feature = "alloc")]
+			Self::UnknownKeys(keys) => {
+				write!(f, "Unknown keys: {}", crate::value::unknown_summary(keys))
+			}
 			Self::NotFound => write!(f, "Not found"),
 			Self::Custom(s) => write!(f, "Custom error: {}", s),
+			#[cfg(feature = "alloc")]
+			Self::PreProcess(msg) => write!(f, "Pre-processing failed: {}", msg),
 			#[cfg(feature = "fs")]
 			Self::SandboxViolation => write!(f, "Sandbox violation"),
 			#[cfg(feature = "validate")]
-			Self::Validation(_) => write!(f, "Validation error"),
+			Self::Validation(..) => write!(f, "Validation error"),
+		}
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl FmtError {
+	/// A parse error with only a message.
+	pub(crate) fn parse(message: impl Into<String>) -> Self {
+		ParseError::new(message).into()
+	}
+
+	/// Records the field path of a failed deserialization, unless already known.
+	pub(crate) fn at_path(mut self, path: Option<String>) -> Self {
+		if let Self::ParseError(e) = &mut self
+			&& e.path.is_none()
+		{
+			e.path = path;
+		}
+		self
+	}
+
+	/// Records the key of the resource being parsed, unless already known.
+	pub(crate) fn in_key(mut self, key: &str) -> Self {
+		if let Self::ParseError(e) = &mut self
+			&& e.key.is_none()
+		{
+			e.key = Some(String::from(key));
+		}
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<ParseError> for FmtError {
+	fn from(e: ParseError) -> Self {
+		Self::ParseError(alloc::boxed::Box::new(e))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl serde::de::Error for FmtError {
+	fn custom<T: core::fmt::Display>(msg: T) -> Self {
+		ParseError::new(alloc::format!("{}", msg)).into()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl serde::ser::Error for FmtError {
+	fn custom<T: core::fmt::Display>(msg: T) -> Self {
+		Self::SerializeError(alloc::format!("{}", msg))
+	}
+}
+
+/// A parse failure with as much context as the format reports.
+///
+/// `Display` renders the message followed by a caret snippet of the
+/// offending line when the location is known:
+///
+/// ```text
+/// parse error: invalid type: string "80", expected u16
+///  --> config.toml:3:8 (toml)
+///   |
+/// 3 | port = "80"
+///   |        ^^^^
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseError {
+	pub message: String,
+	/// Key of the resource that failed to parse, e.g. `config.toml`.
+	pub key: Option<String>,
+	/// Field path of the failing value, e.g. `servers[2].tls.port`.
+	pub path: Option<String>,
+	/// Name of the format, e.g. `toml`.
+	pub format: Option<&'static str>,
+	/// 1-based line number.
+	pub line: Option<usize>,
+	/// 1-based column, counted in characters.
+	pub column: Option<usize>,
+	/// Byte range of the offending input.
+	pub span: Option<Range<usize>>,
+	/// The full text of the offending line.
+	pub source_line: Option<String>,
+	/// The whole input `span` refers to, kept for rendering diagnostics.
+	#[cfg(feature = "diagnostics")]
+	pub source: Option<String>,
+}
+
+#[cfg(feature = "alloc")]
+impl ParseError {
+	pub fn new(message: impl Into<String>) -> Self {
+		Self {
+			message: message.into(),
+			..Self::default()
+		}
+	}
+
+	pub fn with_format(mut self, format: &'static str) -> Self {
+		self.format = Some(format);
+		self
+	}
+
+	pub fn with_key(mut self, key: impl Into<String>) -> Self {
+		self.key = Some(key.into());
+		self
+	}
+
+	pub fn with_path(mut self, path: impl Into<String>) -> Self {
+		self.path = Some(path.into());
+		self
+	}
+
+	/// Locates the error at the byte range `span` of `input`.
+	pub fn with_span(mut self, input: &[u8], span: Range<usize>) -> Self {
+		let text = String::from_utf8_lossy(input);
+		let start = floor_char_boundary(&text, span.start);
+		let (line, column) = crate::provenance::line_column(&text, start);
+		let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+		let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+
+		self.line = Some(line);
+		self.column = Some(column);
+		self.source_line = Some(String::from(
+			text[line_start..line_end].trim_end_matches('\r'),
+		));
+		self.span = Some(start..span.end.max(start));
+		#[cfg(feature = "diagnostics")]
+		{
+			self.source = Some(text.into_owned());
+		}
+		self
+	}
+
+	/// Locates the error at a 1-based `line` and `column` of `input`.
+	pub fn with_line_column(self, input: &[u8], line: usize, column: usize) -> Self {
+		let text = String::from_utf8_lossy(input);
+		let line_start = if line <= 1 {
+			0
+		} else {
+			text
+				.match_indices('\n')
+				.nth(line - 2)
+				.map_or(text.len(), |(i, _)| i + 1)
+		};
+		let offset = text[line_start..]
+			.char_indices()
+			.nth(column.saturating_sub(1))
+			.map_or(text.len(), |(i, _)| line_start + i);
+		self.with_span(input, offset..offset)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "parse error")?;
+		if let Some(path) = &self.path {
+			write!(f, " at `{}`", path)?;
+		}
+		write!(f, ": {}", self.message)?;
+
+		if self.key.is_none() && self.line.is_none() {
+			return Ok(());
+		}
+		write!(f, "\n --> {}", self.key.as_deref().unwrap_or("<input>"))?;
+		if let (Some(line), Some(column)) = (self.line, self.column) {
+			write!(f, ":{}:{}", line, column)?;
+		}
+		if let Some(format) = self.format {
+			write!(f, " ({})", format)?;
+		}
+
+		let (Some(line), Some(column), Some(source_line)) = (self.line, self.column, &self.source_line)
+		else {
+			return Ok(());
+		};
+		let number = alloc::format!("{}", line);
+		let gutter = " ".repeat(number.len());
+		let mut marker: String = source_line
+			.chars()
+			.take(column.saturating_sub(1))
+			.map(|c| if c == '\t' { '\t' } else { ' ' })
+			.collect();
+		let rest = source_line
+			.chars()
+			.count()
+			.saturating_sub(column.saturating_sub(1));
+		let width = self
+			.span
+			.as_ref()
+			.map_or(1, |span| span.len())
+			.clamp(1, rest.max(1));
+		marker.push_str(&"^".repeat(width));
+		write!(
+			f,
+			"\n{gutter} |\n{number} | {source_line}\n{gutter} | {marker}"
+		)
+	}
+}
+
+/// The largest char boundary of `text` at or before `index`.
+#[cfg(feature = "alloc")]
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+	let mut index = index.min(text.len());
+	while !text.is_char_boundary(index) {
+		index -= 1;
+	}
+	index
+}
+
+/// What kind of problem an `ErrorItem` describes.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+	/// A document failed to parse or to deserialize into the target type.
+	Parse,
+	/// A key matches no field of the target type.
+	UnknownKey,
+	/// A `${...}` placeholder could not be expanded.
+	Interpolation,
+	/// The document violates the loader's JSON Schema.
+	Schema,
+	/// A `validator` rule failed.
+	Validation,
+	/// Any other failure, such as an unreadable include.
+	Other,
+}
+
+/// A single problem found while loading, with the file and field it concerns.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorItem {
+	pub kind: ErrorKind,
+	/// Key of the file the problem was found in, if known.
+	pub file: Option<String>,
+	/// 1-based line, where the format reports positions.
+	pub line: Option<usize>,
+	/// 1-based column, where the format reports positions.
+	pub column: Option<usize>,
+	/// Field path, e.g. `servers[0].port`.
+	pub path: Option<String>,
+	pub message: String,
+}
+
+#[cfg(feature = "alloc")]
+impl ErrorItem {
+	fn new(kind: ErrorKind, path: Option<String>, message: impl Into<String>) -> Self {
+		Self {
+			kind,
+			file: None,
+			line: None,
+			column: None,
+			path,
+			message: message.into(),
+		}
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ErrorItem {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(file) = &self.file {
+			write!(f, "{}", file)?;
+			if let (Some(line), Some(column)) = (self.line, self.column) {
+				write!(f, ":{}:{}", line, column)?;
+			}
+			write!(f, ": ")?;
+		}
+		if let Some(path) = self.path.as_deref().filter(|path| !path.is_empty()) {
+			write!(f, "{}: ", path)?;
+		}
+		write!(f, "{}", self.message)
+	}
+}
+
+/// Every problem found by a load in accumulate mode.
+///
+/// `Display` renders a summary with one line per problem:
+///
+/// ```text
+/// 2 problems found:
+///   config.toml:3:8: server.port: invalid type: string "80", expected u16
+///   config.toml:4:1: server.hots: unknown key (did you mean `host`?)
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FmtErrors {
+	items: Vec<ErrorItem>,
+}
+
+#[cfg(feature = "alloc")]
+impl FmtErrors {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	pub fn iter(&self) -> core::slice::Iter<'_, ErrorItem> {
+		self.items.iter()
+	}
+
+	/// Adds the problems described by `error`.
+	///
+	/// Errors that group several problems (unknown keys, schema violations,
+	/// validation errors, `FmtError::Multiple`) add one item each.
+	pub fn push(&mut self, error: FmtError) {
+		match error {
+			FmtError::ParseError(e) => {
+				let e = *e;
+				self.items.push(ErrorItem {
+					file: e.key,
+					line: e.line,
+					column: e.column,
+					..ErrorItem::new(ErrorKind::Parse, e.path, e.message)
+				});
+			}
+			FmtError::UnknownKeys(keys) => {
+				for key in keys {
+					let mut message = String::from("unknown key");
+					if let Some(suggestion) = key.suggestion {
+						message.push_str(&alloc::format!(" (did you mean `{}`?)", suggestion));
+					}
+					self.items.push(ErrorItem {
+						file: key.file,
+						line: key.line,
+						column: key.column,
+						..ErrorItem::new(ErrorKind::UnknownKey, Some(key.path), message)
+					});
+				}
+			}
+			#[cfg(feature = "jsonschema")]
+			FmtError::Schema(violations) => {
+				for violation in violations {
+					let path = (!violation.path.is_empty()).then_some(violation.path);
+					self
+						.items
+						.push(ErrorItem::new(ErrorKind::Schema, path, violation.message));
+				}
+			}
+			#[cfg(feature = "std")]
+			FmtError::Interpolation { file, key, message } => {
+				self.items.push(ErrorItem {
+					file: (!file.is_empty()).then_some(file),
+					..ErrorItem::new(ErrorKind::Interpolation, Some(key), message)
+				});
+			}
+			#[cfg(all(feature = "validate", feature = "std"))]
+			FmtError::Validation(errors) => self.push_validation(&errors, ""),
+			#[cfg(all(feature = "validate", not(feature = "std")))]
+			FmtError::Validation() => {
+				self.items.push(ErrorItem::new(
+					ErrorKind::Validation,
+					None,
+					"validation failed",
+				));
+			}
+			FmtError::Multiple(errors) => self.items.extend(errors.items),
+			other => {
+				self
+					.items
+					.push(ErrorItem::new(ErrorKind::Other, None, other.to_string()));
+			}
+		}
+	}
+
+	/// Adds one item per failed rule, ordered by field path.
+	#[cfg(all(feature = "validate", feature = "std"))]
+	fn push_validation(&mut self, errors: &validator::ValidationErrors, path: &str) {
+		use validator::ValidationErrorsKind;
+
+		let mut fields: Vec<_> = errors.errors().iter().collect();
+		fields.sort_by(|a, b| a.0.cmp(b.0));
+		for (field, kind) in fields {
+			// Struct-level rules are reported under `__all__`.
+			let path = match (path, field.as_ref()) {
+				(path, "__all__") => String::from(path),
+				("", field) => String::from(field),
+				(path, field) => alloc::format!("{}.{}", path, field),
+			};
+			match kind {
+				ValidationErrorsKind::Field(failures) => {
+					for failure in failures {
+						self.items.push(ErrorItem::new(
+							ErrorKind::Validation,
+							(!path.is_empty()).then(|| path.clone()),
+							validation_message(failure),
+						));
+					}
+				}
+				ValidationErrorsKind::Struct(nested) => self.push_validation(nested, &path),
+				ValidationErrorsKind::List(items) => {
+					for (index, nested) in items {
+						self.push_validation(nested, &alloc::format!("{}[{}]", path, index));
+					}
+				}
+			}
+		}
+	}
+
+	/// Fills the file and position of items that only know their field path
+	/// from the origins of the merged document.
+	pub(crate) fn locate(&mut self, origins: &crate::provenance::Origins) {
+		use crate::provenance::{self, OriginKind};
+
+		for item in &mut self.items {
+			let origin = item
+				.path
+				.as_deref()
+				.and_then(|path| provenance::lookup(origins, path))
+				.filter(|origin| origin.kind == OriginKind::File);
+			if let Some(origin) = origin
+				&& item.file.is_none()
+			{
+				item.file = Some(origin.source.clone());
+				item.line = origin.line;
+				item.column = origin.column;
+			}
+		}
+	}
+}
+
+/// The custom message of a failed rule, or its code and parameters.
+#[cfg(all(feature = "validate", feature = "std"))]
+fn validation_message(error: &validator::ValidationError) -> String {
+	if let Some(message) = &error.message {
+		return message.to_string();
+	}
+	let mut params: Vec<_> = error.params.iter().collect();
+	if params.is_empty() {
+		return alloc::format!("failed `{}`", error.code);
+	}
+	params.sort_by(|a, b| a.0.cmp(b.0));
+	let params: Vec<_> = params
+		.into_iter()
+		.map(|(name, value)| alloc::format!("{} = {}", name, value))
+		.collect();
+	alloc::format!("failed `{}` ({})", error.code, params.join(", "))
+}
+
+#[cfg(feature = "alloc")]
+impl From<FmtError> for FmtErrors {
+	fn from(error: FmtError) -> Self {
+		let mut errors = Self::new();
+		errors.push(error);
+		errors
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl IntoIterator for FmtErrors {
+	type Item = ErrorItem;
+	type IntoIter = alloc::vec::IntoIter<ErrorItem>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.items.into_iter()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a FmtErrors {
+	type Item = &'a ErrorItem;
+	type IntoIter = core::slice::Iter<'a, ErrorItem>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.items.iter()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for FmtErrors {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.items.len() {
+			1 => write!(f, "1 problem found:")?,
+			n => write!(f, "{} problems found:", n)?,
+		}
+		for item in &self.items {
+			write!(f, "\n  {}", item)?;
 		}
+		Ok(())
 	}
 }
@@ -0,0 +1,371 @@
+/* src/format/edit.rs */
+
+use super::Toml;
+use crate::value::{Segment, Value, parse_path};
+use crate::{FmtError, Format, ParseError, ValidateConfig};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use toml_edit::{Array, ArrayOfTables, Decor, DocumentMut, InlineTable, Item, RawString, Table};
+
+/// An editable TOML document that keeps comments, key order and formatting.
+///
+/// Obtained with `DynLoader::edit_toml` and written back with
+/// `DynLoader::commit_toml`, which only writes edits that still deserialize
+/// into the configuration type.
+#[derive(Debug, Clone)]
+pub struct TomlEditor {
+	key: String,
+	doc: DocumentMut,
+}
+
+impl TomlEditor {
+	/// Parses the document stored at `key`.
+	pub fn new(key: impl Into<String>, input: &[u8]) -> Result<Self, FmtError> {
+		let key = key.into();
+		let s = core::str::from_utf8(input).map_err(|e| {
+			ParseError::new(alloc::format!("{}", e))
+				.with_format("toml")
+				.with_key(&*key)
+		})?;
+		let doc = s.parse::<DocumentMut>().map_err(|e| {
+			let error = ParseError::new(e.message())
+				.with_format("toml")
+				.with_key(&*key);
+			match e.span() {
+				Some(span) => error.with_span(input, span),
+				None => error,
+			}
+		})?;
+		Ok(Self { key, doc })
+	}
+
+	/// Creates a document from a table, laid out like `format` leaves it.
+	pub fn from_value(key: impl Into<String>, value: Value) -> Result<Self, FmtError> {
+		let Item::Table(table) = to_item(value, true)? else {
+			return Err(FmtError::SerializeError(String::from(
+				"a TOML document must be a table",
+			)));
+		};
+		let mut editor = Self {
+			key: key.into(),
+			doc: DocumentMut::from(table),
+		};
+		editor.format();
+		Ok(editor)
+	}
+
+	/// The key the document was read from.
+	pub fn key(&self) -> &str {
+		&self.key
+	}
+
+	/// Sets the value at a dotted path such as `server.port` or `servers[1].host`.
+	///
+	/// Missing tables are created. An existing value keeps its surrounding
+	/// comments and whitespace; an index one past the end appends to an array.
+	pub fn set(&mut self, path: &str, value: impl Into<Value>) -> Result<(), FmtError> {
+		let value = value.into();
+		let (last, item) = self.parent(path, true)?;
+		let not_found = || FmtError::PathNotFound(String::from(path));
+
+		match last {
+			Segment::Key(key) => {
+				let standard = item.is_table();
+				let table = item.as_table_like_mut().ok_or_else(not_found)?;
+				let mut new = to_item(value, standard)?;
+				match table.get_mut(&key) {
+					// Replaced in place, so the key keeps its comments too.
+					Some(old) => {
+						if let (Item::Value(old), Item::Value(new)) = (&*old, &mut new) {
+							*new.decor_mut() = old.decor().clone();
+						}
+						*old = new;
+					}
+					None => {
+						table.insert(&key, new);
+					}
+				}
+			}
+			Segment::Index(index) => {
+				if let Some(tables) = item.as_array_of_tables_mut() {
+					let Item::Table(table) = to_item(value, true)? else {
+						return Err(FmtError::SerializeError(String::from(
+							"array of tables only holds tables",
+						)));
+					};
+					let len = tables.len();
+					match tables.get_mut(index) {
+						Some(old) => *old = table,
+						None if index == len => tables.push(table),
+						None => return Err(not_found()),
+					}
+				} else {
+					let array = item.as_array_mut().ok_or_else(not_found)?;
+					let mut new = to_value(value)?;
+					match array.get(index) {
+						Some(old) => {
+							*new.decor_mut() = old.decor().clone();
+							array.replace_formatted(index, new);
+						}
+						None if index == array.len() => array.push(new),
+						None => return Err(not_found()),
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Removes the value at a dotted path.
+	pub fn remove(&mut self, path: &str) -> Result<(), FmtError> {
+		let (last, item) = self.parent(path, false)?;
+		let not_found = || FmtError::PathNotFound(String::from(path));
+
+		match last {
+			Segment::Key(key) => {
+				let table = item.as_table_like_mut().ok_or_else(not_found)?;
+				table.remove(&key).ok_or_else(not_found)?;
+			}
+			Segment::Index(index) => {
+				if let Some(tables) = item.as_array_of_tables_mut() {
+					if index >= tables.len() {
+						return Err(not_found());
+					}
+					tables.remove(index);
+				} else {
+					let array = item.as_array_mut().ok_or_else(not_found)?;
+					if index >= array.len() {
+						return Err(not_found());
+					}
+					array.remove(index);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Rewrites the document in canonical form: keys and tables sorted,
+	/// whitespace normalized, and arrays and inline tables on one line.
+	///
+	/// Comments move with the entry they belong to; an array containing
+	/// comments is left as written.
+	pub fn format(&mut self) {
+		format_table(self.doc.as_table_mut(), &mut 0);
+		if !is_comment(self.doc.trailing()) {
+			self.doc.set_trailing("");
+		}
+	}
+
+	/// Deserializes and validates the edited document as `T`.
+	pub fn check<T>(&self) -> Result<T, FmtError>
+	where
+		T: DeserializeOwned + ValidateConfig,
+	{
+		let value: T = Toml.parse(self.doc.to_string().as_bytes())?;
+		value.validate_config()?;
+		Ok(value)
+	}
+
+	/// Renders the edited document.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		self.doc.to_string().into_bytes()
+	}
+
+	/// Walks to the container of the last path segment.
+	///
+	/// With `create`, missing tables along the way are inserted.
+	fn parent(&mut self, path: &str, create: bool) -> Result<(Segment, &mut Item), FmtError> {
+		let mut segments = parse_path(path)?;
+		let last = segments
+			.pop()
+			.ok_or_else(|| FmtError::InvalidPath(String::from(path)))?;
+		let not_found = || FmtError::PathNotFound(String::from(path));
+
+		let mut item = self.doc.as_item_mut();
+		for segment in segments {
+			item = match segment {
+				Segment::Key(key) => {
+					let standard = item.is_table();
+					let table = item.as_table_like_mut().ok_or_else(not_found)?;
+					if create && !table.contains_key(&key) {
+						let new = if standard {
+							let mut new = Table::new();
+							new.set_implicit(true);
+							Item::Table(new)
+						} else {
+							Item::Value(InlineTable::new().into())
+						};
+						table.insert(&key, new);
+					}
+					table.get_mut(&key).ok_or_else(not_found)?
+				}
+				Segment::Index(index) => item.get_mut(index).ok_or_else(not_found)?,
+			};
+		}
+		Ok((last, item))
+	}
+}
+
+impl core::fmt::Display for TomlEditor {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		self.doc.fmt(f)
+	}
+}
+
+/// Sorts a table and the tables below it, numbering their headers in
+/// sorted order from `position`.
+fn format_table(table: &mut Table, position: &mut isize) {
+	table.set_position(*position);
+	*position += 1;
+	normalize(table.decor_mut(), None);
+	table.sort_values();
+	for (mut key, item) in table.iter_mut() {
+		// A key's suffix sits before `=`, or before `]` in a table header
+		let suffix = if item.is_value() { " " } else { "" };
+		normalize(key.leaf_decor_mut(), Some(suffix));
+		key.dotted_decor_mut().clear();
+		match item {
+			Item::Value(value) => format_value(value),
+			Item::Table(table) => format_table(table, position),
+			Item::ArrayOfTables(tables) => {
+				for table in tables.iter_mut() {
+					format_table(table, position);
+				}
+			}
+			Item::None => {}
+		}
+	}
+}
+
+fn format_value(value: &mut toml_edit::Value) {
+	let decor = value.decor_mut();
+	let comment = comment(decor.suffix()).map(|comment| alloc::format!(" {}", comment));
+	decor.clear();
+	if let Some(comment) = comment {
+		decor.set_suffix(comment);
+	}
+	match value {
+		toml_edit::Value::Array(array) => {
+			let commented =
+				is_comment(array.trailing()) || array.iter().any(|item| is_commented(item.decor()));
+			if !commented {
+				array.fmt();
+				array.iter_mut().for_each(format_value);
+			}
+		}
+		toml_edit::Value::InlineTable(table) => {
+			table.fmt();
+			table.sort_values();
+			for (_, value) in table.iter_mut() {
+				format_value(value);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Resets the whitespace around a key or table header, keeping the comment
+/// lines before it. `suffix` replaces what follows it.
+fn normalize(decor: &mut Decor, suffix: Option<&str>) {
+	// Blank lines before the comments collapse into one
+	let prefix = decor
+		.prefix()
+		.and_then(RawString::as_str)
+		.filter(|s| s.contains('#'))
+		.map(|s| {
+			let comments = s.trim_start_matches([' ', '\t', '\r', '\n']);
+			let blank = s[..s.len() - comments.len()].contains('\n');
+			alloc::format!("{}{}", if blank { "\n" } else { "" }, comments)
+		});
+	let suffix = match suffix {
+		Some(suffix) => Some(String::from(suffix)),
+		None => comment(decor.suffix()).map(|comment| alloc::format!(" {}", comment)),
+	};
+	decor.clear();
+	if let Some(prefix) = prefix {
+		decor.set_prefix(prefix);
+	}
+	if let Some(suffix) = suffix {
+		decor.set_suffix(suffix);
+	}
+}
+
+/// The comment in trailing whitespace, if any.
+fn comment(raw: Option<&RawString>) -> Option<&str> {
+	raw?.as_str().map(str::trim).filter(|s| s.starts_with('#'))
+}
+
+fn is_commented(decor: &Decor) -> bool {
+	decor.prefix().is_some_and(is_comment) || decor.suffix().is_some_and(is_comment)
+}
+
+fn is_comment(raw: &RawString) -> bool {
+	raw.as_str().is_some_and(|s| s.contains('#'))
+}
+
+/// Converts a value for a table entry; `standard` selects `[table]` headers
+/// over inline tables.
+fn to_item(value: Value, standard: bool) -> Result<Item, FmtError> {
+	match value {
+		Value::Table(map) if standard => {
+			let mut table = Table::new();
+			for (key, value) in map {
+				table.insert(&key, to_item(value, true)?);
+			}
+			Ok(Item::Table(table))
+		}
+		Value::Array(items)
+			if standard
+				&& !items.is_empty()
+				&& items.iter().all(|item| matches!(item, Value::Table(_))) =>
+		{
+			let mut tables = ArrayOfTables::new();
+			for item in items {
+				if let Item::Table(table) = to_item(item, true)? {
+					tables.push(table);
+				}
+			}
+			Ok(Item::ArrayOfTables(tables))
+		}
+		value => Ok(Item::Value(to_value(value)?)),
+	}
+}
+
+fn to_value(value: Value) -> Result<toml_edit::Value, FmtError> {
+	Ok(match value {
+		Value::Null => {
+			return Err(FmtError::SerializeError(String::from(
+				"TOML cannot represent null",
+			)));
+		}
+		Value::Bool(b) => b.into(),
+		Value::Integer(i) => i.into(),
+		Value::UInteger(i) => {
+			return Err(FmtError::SerializeError(alloc::format!(
+				"TOML integers are signed 64-bit, {} is out of range",
+				i
+			)));
+		}
+		Value::Float(f) => f.into(),
+		Value::String(s) => s.into(),
+		Value::Datetime(s) => s
+			.parse::<toml_edit::Datetime>()
+			.map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))?
+			.into(),
+		Value::Array(items) => {
+			let mut array = Array::new();
+			for item in items {
+				array.push(to_value(item)?);
+			}
+			array.into()
+		}
+		Value::Table(map) => {
+			let mut table = InlineTable::new();
+			for (key, value) in map {
+				table.insert(key, to_value(value)?);
+			}
+			table.into()
+		}
+	})
+}
@@ -1,7 +1,17 @@
 /* src/format/json.rs */
 
+#[cfg(feature = "alloc")]
+use super::Style;
+#[cfg(feature = "alloc")]
+use crate::ParseError;
+#[cfg(feature = "alloc")]
+use crate::value::deserialize_tracked;
 use crate::{FmtError, Format};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use serde::de::DeserializeOwned;
+#[cfg(feature = "alloc")]
+use serde::ser::Serialize;
 
 /// JSON format parser using `serde_json`.
 pub struct Json;
@@ -11,17 +21,38 @@ impl Format for Json {
 		&["json"]
 	}
 
+	fn self_describing(&self) -> bool {
+		true
+	}
+
+	#[cfg(feature = "alloc")]
 	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
-		serde_json::from_slice(input).map_err(|e| {
-			#[cfg(feature = "alloc")]
-			{
-				FmtError::ParseError(alloc::format!("{}", e))
-			}
-			#[cfg(not(feature = "alloc"))]
-			{
-				_ = e;
-				FmtError::ParseError
+		let mut de = serde_json::Deserializer::from_slice(input);
+		let result = deserialize_tracked(&mut de).and_then(|value| {
+			de.end().map_err(|e| (e, None))?;
+			Ok(value)
+		});
+		result.map_err(|(e, path)| {
+			let mut error = ParseError::new(super::strip_location(&e)).with_format("json");
+			error.path = path;
+			match e.line() {
+				0 => error.into(),
+				line => error.with_line_column(input, line, e.column()).into(),
 			}
 		})
 	}
+
+	#[cfg(not(feature = "alloc"))]
+	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
+		serde_json::from_slice(input).map_err(|_| FmtError::ParseError)
+	}
+
+	#[cfg(feature = "alloc")]
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T, style: Style) -> Result<Vec<u8>, FmtError> {
+		match style {
+			Style::Compact => serde_json::to_vec(value),
+			Style::Pretty => serde_json::to_vec_pretty(value),
+		}
+		.map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))
+	}
 }
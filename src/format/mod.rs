@@ -1,7 +1,11 @@
 /* src/format/mod.rs */
 
 use crate::{FmtError, Format};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use serde::de::DeserializeOwned;
+#[cfg(feature = "alloc")]
+use serde::ser::Serialize;
 
 #[cfg(feature = "json")]
 mod json;
@@ -13,6 +17,11 @@ mod toml;
 #[cfg(feature = "toml")]
 pub use toml::Toml;
 
+#[cfg(feature = "edit")]
+mod edit;
+#[cfg(feature = "edit")]
+pub use edit::TomlEditor;
+
 #[cfg(feature = "yaml")]
 mod yaml;
 #[cfg(feature = "yaml")]
@@ -23,6 +32,31 @@ mod postcard;
 #[cfg(feature = "postcard")]
 pub use self::postcard::Postcard;
 
+#[cfg(all(feature = "postcard", feature = "alloc"))]
+mod named;
+
+/// Returns the message of a backend error without the trailing
+/// `at line N column M` that its `Display` appends.
+#[cfg(any(feature = "json", feature = "yaml"))]
+#[cfg(feature = "alloc")]
+fn strip_location(e: &impl core::fmt::Display) -> alloc::string::String {
+	let mut message = alloc::format!("{}", e);
+	if let Some(index) = message.rfind(" at line ") {
+		message.truncate(index);
+	}
+	message
+}
+
+/// Layout of serialized output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+	/// Minimal whitespace, e.g. single-line JSON.
+	Compact,
+	/// Indented, human-readable output.
+	#[default]
+	Pretty,
+}
+
 /// An enum wrapper for all supported formats, enabling dynamic dispatch-like behavior.
 #[derive(Debug, Clone, Copy)]
 pub enum AnyFormat {
@@ -36,6 +70,39 @@ pub enum AnyFormat {
 	Postcard,
 }
 
+impl AnyFormat {
+	/// Every format enabled by the crate features.
+	pub const ALL: &'static [AnyFormat] = &[
+		#[cfg(feature = "json")]
+		Self::Json,
+		#[cfg(feature = "toml")]
+		Self::Toml,
+		#[cfg(feature = "yaml")]
+		Self::Yaml,
+		#[cfg(feature = "postcard")]
+		Self::Postcard,
+	];
+
+	/// Returns the enabled format handling the file extension `ext` (without the dot).
+	pub fn from_extension(ext: &str) -> Option<Self> {
+		Self::ALL
+			.iter()
+			.find(|format| format.extensions().contains(&ext))
+			.copied()
+	}
+
+	/// Returns the position of every value, for formats that expose them.
+	#[cfg(feature = "alloc")]
+	pub(crate) fn positions(&self, _input: &[u8]) -> crate::provenance::Positions {
+		match self {
+			#[cfg(feature = "toml")]
+			Self::Toml => self::toml::positions(_input),
+			#[allow(unreachable_patterns)]
+			_ => crate::provenance::Positions::new(),
+		}
+	}
+}
+
 impl Format for AnyFormat {
 	fn extensions(&self) -> &'static [&'static str] {
 		match self {
@@ -57,6 +124,14 @@ impl Format for AnyFormat {
 		}
 	}
 
+	fn self_describing(&self) -> bool {
+		#[cfg(feature = "postcard")]
+		if matches!(self, Self::Postcard) {
+			return false;
+		}
+		true
+	}
+
 	fn parse<T: DeserializeOwned>(&self, _input: &[u8]) -> Result<T, FmtError> {
 		match self {
 			#[cfg(feature = "json")]
@@ -76,4 +151,29 @@ impl Format for AnyFormat {
 			_ => unreachable!(),
 		}
 	}
+
+	#[cfg(feature = "alloc")]
+	fn serialize<T: Serialize + ?Sized>(
+		&self,
+		_value: &T,
+		_style: Style,
+	) -> Result<Vec<u8>, FmtError> {
+		match self {
+			#[cfg(feature = "json")]
+			Self::Json => Json.serialize(_value, _style),
+			#[cfg(feature = "toml")]
+			Self::Toml => Toml.serialize(_value, _style),
+			#[cfg(feature = "yaml")]
+			Self::Yaml => Yaml.serialize(_value, _style),
+			#[cfg(feature = "postcard")]
+			Self::Postcard => Postcard.serialize(_value, _style),
+			#[cfg(not(any(
+				feature = "json",
+				feature = "toml",
+				feature = "yaml",
+				feature = "postcard"
+			)))]
+			_ => unreachable!(),
+		}
+	}
 }
@@ -0,0 +1,302 @@
+/* src/format/named.rs */
+
+use serde::de::{
+	self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+	VariantAccess, Visitor,
+};
+
+/// Wraps the deserializer of a format that encodes structs as plain
+/// sequences, so that their fields are visited as a map keyed by field name.
+///
+/// Failure paths then name struct fields (`servers[2].tls.port`) instead of
+/// indexing them (`[0][2][1][0]`).
+pub(crate) struct Named<D>(pub(crate) D);
+
+macro_rules! forward_deserialize {
+	($($method:ident)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+				self.0.$method(Wrap::new(visitor))
+			}
+		)*
+	};
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Named<D> {
+	type Error = D::Error;
+
+	forward_deserialize! {
+		deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+		deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+		deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+		deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+		deserialize_option deserialize_unit deserialize_seq deserialize_map
+		deserialize_identifier deserialize_ignored_any
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.0.deserialize_unit_struct(name, Wrap::new(visitor))
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.0.deserialize_newtype_struct(name, Wrap::new(visitor))
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.0.deserialize_tuple(len, Wrap::new(visitor))
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self
+			.0
+			.deserialize_tuple_struct(name, len, Wrap::new(visitor))
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self
+			.0
+			.deserialize_struct(name, fields, Wrap { visitor, fields })
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.0.deserialize_enum(name, variants, Wrap::new(visitor))
+	}
+
+	fn is_human_readable(&self) -> bool {
+		self.0.is_human_readable()
+	}
+}
+
+/// Passes everything the format visits on to `visitor`, keeping nested
+/// values wrapped. A sequence visited for a struct with `fields` is turned
+/// into a map.
+struct Wrap<V> {
+	visitor: V,
+	fields: &'static [&'static str],
+}
+
+impl<V> Wrap<V> {
+	fn new(visitor: V) -> Self {
+		Self {
+			visitor,
+			fields: &[],
+		}
+	}
+}
+
+macro_rules! forward_visit {
+	($($method:ident($ty:ty))*) => {
+		$(
+			fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+				self.visitor.$method(v)
+			}
+		)*
+	};
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrap<V> {
+	type Value = V::Value;
+
+	fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		self.visitor.expecting(f)
+	}
+
+	forward_visit! {
+		visit_bool(bool) visit_i8(i8) visit_i16(i16) visit_i32(i32) visit_i64(i64)
+		visit_i128(i128) visit_u8(u8) visit_u16(u16) visit_u32(u32) visit_u64(u64)
+		visit_u128(u128) visit_f32(f32) visit_f64(f64) visit_char(char)
+		visit_str(&str) visit_borrowed_str(&'de str) visit_string(alloc::string::String)
+		visit_bytes(&[u8]) visit_borrowed_bytes(&'de [u8]) visit_byte_buf(alloc::vec::Vec<u8>)
+	}
+
+	fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+		self.visitor.visit_none()
+	}
+
+	fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+		self.visitor.visit_unit()
+	}
+
+	fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+		self.visitor.visit_some(Named(deserializer))
+	}
+
+	fn visit_newtype_struct<D: Deserializer<'de>>(
+		self,
+		deserializer: D,
+	) -> Result<V::Value, D::Error> {
+		self.visitor.visit_newtype_struct(Named(deserializer))
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+		if self.fields.is_empty() {
+			self.visitor.visit_seq(Seq(seq))
+		} else {
+			self.visitor.visit_map(Fields {
+				seq,
+				fields: self.fields,
+				index: 0,
+			})
+		}
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+		self.visitor.visit_map(Map(map))
+	}
+
+	fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+		self.visitor.visit_enum(Enum(data))
+	}
+}
+
+struct Seed<S>(S);
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Seed<S> {
+	type Value = S::Value;
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+		self.0.deserialize(Named(deserializer))
+	}
+}
+
+struct Seq<A>(A);
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Seq<A> {
+	type Error = A::Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, A::Error> {
+		self.0.next_element_seed(Seed(seed))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		self.0.size_hint()
+	}
+}
+
+/// The elements of a struct's sequence, keyed by the struct's field names.
+struct Fields<A> {
+	seq: A,
+	fields: &'static [&'static str],
+	index: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> MapAccess<'de> for Fields<A> {
+	type Error = A::Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, A::Error> {
+		let Some(field) = self.fields.get(self.index) else {
+			return Ok(None);
+		};
+		seed.deserialize(field.into_deserializer()).map(Some)
+	}
+
+	fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+		let index = self.index;
+		self.index += 1;
+		self
+			.seq
+			.next_element_seed(Seed(seed))?
+			.ok_or_else(|| de::Error::invalid_length(index, &"a struct field"))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.fields.len() - self.index)
+	}
+}
+
+struct Map<A>(A);
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Map<A> {
+	type Error = A::Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, A::Error> {
+		self.0.next_key_seed(Seed(seed))
+	}
+
+	fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+		self.0.next_value_seed(Seed(seed))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		self.0.size_hint()
+	}
+}
+
+struct Enum<A>(A);
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Enum<A> {
+	type Error = A::Error;
+	type Variant = Variant<A::Variant>;
+
+	fn variant_seed<S: DeserializeSeed<'de>>(
+		self,
+		seed: S,
+	) -> Result<(S::Value, Self::Variant), A::Error> {
+		self
+			.0
+			.variant_seed(Seed(seed))
+			.map(|(value, variant)| (value, Variant(variant)))
+	}
+}
+
+struct Variant<A>(A);
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Variant<A> {
+	type Error = A::Error;
+
+	fn unit_variant(self) -> Result<(), A::Error> {
+		self.0.unit_variant()
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+		self.0.newtype_variant_seed(Seed(seed))
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+		self.0.tuple_variant(len, Wrap::new(visitor))
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, A::Error> {
+		self.0.struct_variant(fields, Wrap { visitor, fields })
+	}
+}
@@ -1,7 +1,19 @@
 /* src/format/postcard.rs */
 
+#[cfg(feature = "alloc")]
+use super::Style;
+#[cfg(feature = "alloc")]
+use super::named::Named;
+#[cfg(feature = "alloc")]
+use crate::ParseError;
+#[cfg(feature = "alloc")]
+use crate::value::deserialize_tracked;
 use crate::{FmtError, Format};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use serde::de::DeserializeOwned;
+#[cfg(feature = "alloc")]
+use serde::ser::Serialize;
 
 /// Postcard format parser using `postcard`.
 pub struct Postcard;
@@ -11,17 +23,27 @@ impl Format for Postcard {
 		&["bin", "post"]
 	}
 
+	/// Struct fields have no names on the wire; failure paths take them from
+	/// the target type, e.g. `servers[2].tls.port`.
+	#[cfg(feature = "alloc")]
 	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
-		postcard::from_bytes(input).map_err(|e| {
-			#[cfg(feature = "alloc")]
-			{
-				FmtError::ParseError(alloc::format!("{}", e))
-			}
-			#[cfg(not(feature = "alloc"))]
-			{
-				_ = e;
-				FmtError::ParseError
-			}
+		let mut de = postcard::Deserializer::from_bytes(input);
+		deserialize_tracked(Named(&mut de)).map_err(|(e, path)| {
+			let mut error = ParseError::new(alloc::format!("{}", e)).with_format("postcard");
+			error.path = path;
+			error.into()
 		})
 	}
+
+	#[cfg(not(feature = "alloc"))]
+	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
+		postcard::from_bytes(input).map_err(|_| FmtError::ParseError)
+	}
+
+	#[cfg(feature = "alloc")]
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T, style: Style) -> Result<Vec<u8>, FmtError> {
+		// Binary output has no layout.
+		_ = style;
+		postcard::to_allocvec(value).map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))
+	}
 }
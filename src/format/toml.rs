@@ -1,7 +1,21 @@
 /* src/format/toml.rs */
 
+#[cfg(feature = "alloc")]
+use super::Style;
+#[cfg(feature = "alloc")]
+use crate::ParseError;
+#[cfg(feature = "alloc")]
+use crate::provenance::{Position, Positions, line_column};
+#[cfg(feature = "alloc")]
+use crate::value::deserialize_tracked;
 use crate::{FmtError, Format};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use serde::de::DeserializeOwned;
+#[cfg(feature = "alloc")]
+use serde::ser::Serialize;
 
 /// TOML format parser using `toml`.
 pub struct Toml;
@@ -11,11 +25,20 @@ impl Format for Toml {
 		&["toml"]
 	}
 
+	fn self_describing(&self) -> bool {
+		true
+	}
+
 	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
 		let s = core::str::from_utf8(input).map_err(|e| {
 			#[cfg(feature = "alloc")]
 			{
-				FmtError::ParseError(alloc::format!("{}", e))
+				let offset = e.valid_up_to();
+				FmtError::from(
+					ParseError::new(alloc::format!("{}", e))
+						.with_format("toml")
+						.with_span(input, offset..offset + 1),
+				)
 			}
 			#[cfg(not(feature = "alloc"))]
 			{
@@ -23,16 +46,90 @@ impl Format for Toml {
 				FmtError::ParseError
 			}
 		})?;
-		toml::from_str(s).map_err(|e| {
-			#[cfg(feature = "alloc")]
-			{
-				FmtError::ParseError(alloc::format!("{}", e))
-			}
-			#[cfg(not(feature = "alloc"))]
-			{
-				_ = e;
-				FmtError::ParseError
+		#[cfg(feature = "alloc")]
+		{
+			let result = toml::Deserializer::parse(s)
+				.map_err(|e| (e, None))
+				.and_then(deserialize_tracked);
+			result.map_err(|(e, path)| {
+				let mut error = ParseError::new(e.message()).with_format("toml");
+				error.path = path;
+				match e.span() {
+					Some(span) => error.with_span(input, span).into(),
+					None => error.into(),
+				}
+			})
+		}
+		#[cfg(not(feature = "alloc"))]
+		toml::from_str(s).map_err(|_| FmtError::ParseError)
+	}
+
+	#[cfg(feature = "alloc")]
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T, style: Style) -> Result<Vec<u8>, FmtError> {
+		match style {
+			Style::Compact => toml::to_string(value),
+			Style::Pretty => toml::to_string_pretty(value),
+		}
+		.map(String::into_bytes)
+		.map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))
+	}
+}
+
+/// Returns the line and column of every value in a TOML document.
+#[cfg(feature = "alloc")]
+pub(crate) fn positions(input: &[u8]) -> Positions {
+	let mut out = Positions::new();
+	let Ok(text) = core::str::from_utf8(input) else {
+		return out;
+	};
+	if let Ok(table) = toml::de::DeTable::parse(text) {
+		collect(table.get_ref(), text, &mut String::new(), &mut out);
+	}
+	out
+}
+
+#[cfg(feature = "alloc")]
+fn collect(table: &toml::de::DeTable<'_>, text: &str, path: &mut String, out: &mut Positions) {
+	for (key, value) in table.iter() {
+		let len = path.len();
+		if !path.is_empty() {
+			path.push('.');
+		}
+		path.push_str(key.get_ref());
+		collect_value(value, key.span(), text, path, out);
+		path.truncate(len);
+	}
+}
+
+#[cfg(feature = "alloc")]
+fn collect_value(
+	value: &toml::Spanned<toml::de::DeValue<'_>>,
+	key: core::ops::Range<usize>,
+	text: &str,
+	path: &mut String,
+	out: &mut Positions,
+) {
+	let span = value.span();
+	let (line, column) = line_column(text, span.start);
+	out.insert(
+		path.clone(),
+		Position {
+			line,
+			column,
+			span,
+			key,
+		},
+	);
+	match value.get_ref() {
+		toml::de::DeValue::Table(inner) => collect(inner, text, path, out),
+		toml::de::DeValue::Array(items) => {
+			for (index, item) in items.iter().enumerate() {
+				let len = path.len();
+				path.push_str(&alloc::format!("[{}]", index));
+				collect_value(item, item.span(), text, path, out);
+				path.truncate(len);
 			}
-		})
+		}
+		_ => {}
 	}
 }
@@ -1,7 +1,12 @@
 /* src/format/yaml.rs */
 
-use crate::{FmtError, Format};
+use super::Style;
+use crate::value::deserialize_tracked;
+use crate::{FmtError, Format, ParseError};
+use alloc::string::String;
+use alloc::vec::Vec;
 use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
 
 /// YAML format parser using `serde_yaml`.
 pub struct Yaml;
@@ -11,17 +16,36 @@ impl Format for Yaml {
 		&["yaml", "yml"]
 	}
 
+	fn self_describing(&self) -> bool {
+		true
+	}
+
 	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
-		serde_yaml::from_slice(input).map_err(|e| {
-			#[cfg(feature = "alloc")]
+		deserialize_tracked(serde_yaml::Deserializer::from_slice(input)).map_err(|(e, path)| {
+			let mut message = super::strip_location(&e);
+			// `serde_yaml` prefixes the message with its own rendering of the path.
+			if let Some(rest) = path
+				.as_deref()
+				.and_then(|path| message.strip_prefix(path)?.strip_prefix(": "))
 			{
-				FmtError::ParseError(alloc::format!("{}", e))
+				message = String::from(rest);
 			}
-			#[cfg(not(feature = "alloc"))]
-			{
-				_ = e;
-				FmtError::ParseError
+			let mut error = ParseError::new(message).with_format("yaml");
+			error.path = path;
+			match e.location() {
+				Some(location) => error
+					.with_line_column(input, location.line(), location.column())
+					.into(),
+				None => error.into(),
 			}
 		})
 	}
+
+	fn serialize<T: Serialize + ?Sized>(&self, value: &T, style: Style) -> Result<Vec<u8>, FmtError> {
+		// YAML output has a single block layout.
+		_ = style;
+		serde_yaml::to_string(value)
+			.map(String::into_bytes)
+			.map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))
+	}
 }
@@ -12,19 +12,52 @@ use alloc::string::String;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 #[cfg(feature = "std")]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
 pub mod error;
 pub mod format;
 pub mod loader;
+#[cfg(feature = "alloc")]
+pub mod provenance;
+#[cfg(feature = "reload")]
+pub mod reload;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod source;
+#[cfg(feature = "alloc")]
+pub mod value;
 
 // Re-export core types
 pub use error::FmtError;
+#[cfg(feature = "alloc")]
+pub use error::{ErrorItem, ErrorKind, FmtErrors, ParseError};
 pub use loader::StaticLoader;
 
 #[cfg(feature = "alloc")]
-pub use loader::DynLoader;
+pub use loader::{DynLoader, Layer, LayeredLoader};
+#[cfg(feature = "alloc")]
+pub use provenance::{Origin, OriginKind};
+#[cfg(feature = "alloc")]
+pub use value::{Change, Diff, UnknownKey, Value};
+
+#[cfg(feature = "std")]
+pub use loader::{EnvOverlay, Interpolator};
+
+#[cfg(feature = "edit")]
+pub use format::TomlEditor;
+
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::FmtDiagnostic;
+
+#[cfg(feature = "jsonschema")]
+pub use loader::{DocumentSchema, SchemaViolation};
+
+#[cfg(feature = "watch")]
+pub use reload::FileWatcher;
+#[cfg(feature = "reload")]
+pub use reload::{ConfigHandle, Poller};
 
 /// Metadata about the loaded resource.
 #[cfg(feature = "std")]
@@ -33,6 +66,15 @@ pub struct LoadInfo {
 	pub path: PathBuf,
 	pub format: &'static str,
 	pub conflicts: Vec<PathBuf>,
+	/// Every resource that contributed to the value, lowest precedence first.
+	pub layers: Vec<PathBuf>,
+	/// The profile that was applied, if any.
+	pub profile: Option<String>,
+	/// Origin of every field path, for self-describing formats.
+	pub origins: provenance::Origins,
+	/// Keys of self-describing documents that match no field of the target
+	/// type. Loaders that deny unknown keys fail instead.
+	pub unknown_keys: Vec<UnknownKey>,
 }
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
@@ -41,6 +83,140 @@ pub struct LoadInfo {
 	pub key: String,
 	pub format: &'static str,
 	pub conflicts: Vec<String>,
+	/// Every resource that contributed to the value, lowest precedence first.
+	pub layers: Vec<String>,
+	/// The profile that was applied, if any.
+	pub profile: Option<String>,
+	/// Origin of every field path, for self-describing formats.
+	pub origins: provenance::Origins,
+	/// Keys of self-describing documents that match no field of the target
+	/// type. Loaders that deny unknown keys fail instead.
+	pub unknown_keys: Vec<UnknownKey>,
+}
+
+#[cfg(feature = "alloc")]
+impl LoadInfo {
+	/// Creates the metadata for a single resource.
+	pub(crate) fn new(key: &str, format: &'static str, conflicts: Vec<String>) -> Self {
+		Self {
+			#[cfg(feature = "std")]
+			path: PathBuf::from(key),
+			#[cfg(not(feature = "std"))]
+			key: String::from(key),
+			format,
+			#[cfg(feature = "std")]
+			conflicts: conflicts.into_iter().map(PathBuf::from).collect(),
+			#[cfg(not(feature = "std"))]
+			conflicts,
+			#[cfg(feature = "std")]
+			layers: alloc::vec![PathBuf::from(key)],
+			#[cfg(not(feature = "std"))]
+			layers: alloc::vec![String::from(key)],
+			profile: None,
+			origins: provenance::Origins::new(),
+			unknown_keys: Vec::new(),
+		}
+	}
+
+	/// Explains where the value at a field path (e.g. `server.port`) came from.
+	///
+	/// Paths without a recorded origin, or with only an ancestor recorded, fall
+	/// back to the closest ancestor; paths absent from every document report
+	/// `OriginKind::Default`.
+	pub fn explain(&self, path: &str) -> provenance::Origin {
+		provenance::lookup(&self.origins, path)
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	/// The key of the resource as a string.
+	pub(crate) fn key(&self) -> alloc::borrow::Cow<'_, str> {
+		#[cfg(feature = "std")]
+		return self.path.to_string_lossy();
+		#[cfg(not(feature = "std"))]
+		return alloc::borrow::Cow::Borrowed(&self.key);
+	}
+
+	/// Records an additional resource with higher precedence than the existing layers.
+	pub(crate) fn push_layer(&mut self, key: &str) {
+		#[cfg(feature = "std")]
+		self.layers.push(PathBuf::from(key));
+		#[cfg(not(feature = "std"))]
+		self.layers.push(String::from(key));
+	}
+}
+
+/// What a loader knows about the resource a value was loaded from, passed to
+/// `PreProcess::set_load_context`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct LoadContext {
+	/// Key of the resource within its source, e.g. `conf/app.toml`.
+	pub key: String,
+	/// The file backing the key for sources on the local file system,
+	/// otherwise the key itself.
+	pub path: PathBuf,
+	pub format: &'static str,
+	/// Root directory of the source, for sources on the local file system.
+	pub source_root: Option<PathBuf>,
+	/// The profile that was applied, if any.
+	pub profile: Option<String>,
+	/// Directory of `path`, for resolving paths relative to the configuration;
+	/// empty if the key has no directory.
+	pub config_dir: PathBuf,
+	pub conflicts: Vec<PathBuf>,
+}
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct LoadContext {
+	/// Key of the resource within its source, e.g. `conf/app.toml`.
+	pub key: String,
+	pub format: &'static str,
+	/// The profile that was applied, if any.
+	pub profile: Option<String>,
+	/// Directory part of the key, for resolving keys relative to the
+	/// configuration; empty if the key has no directory.
+	pub config_dir: String,
+	pub conflicts: Vec<String>,
+}
+
+#[cfg(feature = "alloc")]
+impl LoadContext {
+	/// Describes the resource of `info`, read from `source`.
+	pub(crate) fn new(info: &LoadInfo, source: &dyn Source) -> Self {
+		let key = info.key().into_owned();
+		#[cfg(feature = "std")]
+		{
+			let path = source
+				.local_path(&key)
+				.unwrap_or_else(|| PathBuf::from(&key));
+			let config_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+			Self {
+				key,
+				path,
+				format: info.format,
+				source_root: source.local_root(),
+				profile: info.profile.clone(),
+				config_dir,
+				conflicts: info.conflicts.clone(),
+			}
+		}
+		#[cfg(not(feature = "std"))]
+		{
+			_ = source;
+			let config_dir = key
+				.rfind('/')
+				.map_or(String::new(), |i| String::from(&key[..i]));
+			Self {
+				key,
+				format: info.format,
+				profile: info.profile.clone(),
+				config_dir,
+				conflicts: info.conflicts.clone(),
+			}
+		}
+	}
 }
 
 #[cfg(feature = "alloc")]
@@ -52,6 +228,8 @@ pub use source::FileSource;
 #[cfg(feature = "alloc")]
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
+#[cfg(feature = "alloc")]
+use serde::ser::Serialize;
 
 /// Result of a loading operation.
 #[derive(Debug)]
@@ -69,11 +247,80 @@ pub enum LoadResult<T> {
 }
 
 /// A hook to process data after parsing but before validation.
+///
+/// Loaders call `set_load_context` with what they know about the loaded
+/// resource, then `pre_process`, then validate the value; each stage can be
+/// skipped on the loader. Types whose pre-processing can fail implement
+/// `TryPreProcess` or `AsyncPreProcess` instead.
 pub trait PreProcess {
 	/// Perform data normalization or context injection.
 	fn pre_process(&mut self) {}
 	/// Set context information (e.g., file path or key).
+	///
+	/// Kept for compatibility: the default `set_load_context` passes the key
+	/// of the loaded resource here.
 	fn set_context(&mut self, _ctx: &str) {}
+	/// Set the context of the loaded resource: its key, file path, format,
+	/// source root, profile, directory and conflicts.
+	#[cfg(feature = "alloc")]
+	fn set_load_context(&mut self, ctx: &LoadContext) {
+		self.set_context(&ctx.key);
+	}
+}
+
+/// A pre-processing hook that can reject the loaded data.
+///
+/// Implemented for every `PreProcess` type. A failure ends the load with
+/// `LoadResult::Invalid` carrying the returned error, usually
+/// `FmtError::PreProcess` with a message naming the offending values.
+pub trait TryPreProcess {
+	/// Perform data normalization or consistency checks.
+	fn try_pre_process(&mut self) -> Result<(), FmtError>;
+	/// Set the context of the loaded resource; see `PreProcess::set_load_context`.
+	#[cfg(feature = "alloc")]
+	fn set_load_context(&mut self, _ctx: &LoadContext) {}
+}
+
+impl<T: PreProcess> TryPreProcess for T {
+	fn try_pre_process(&mut self) -> Result<(), FmtError> {
+		self.pre_process();
+		Ok(())
+	}
+
+	#[cfg(feature = "alloc")]
+	fn set_load_context(&mut self, ctx: &LoadContext) {
+		PreProcess::set_load_context(self, ctx);
+	}
+}
+
+/// An asynchronous pre-processing hook that can read further resources, such
+/// as a referenced certificate, through the loader's source.
+///
+/// Only run by `DynLoader::load_with_source`, `StaticLoader::load_with_source`
+/// and the reloading handles; `load` runs the `TryPreProcess` hook and does
+/// not require `Send`. Implemented for every `Send` `TryPreProcess` type. A
+/// failure ends the load with `LoadResult::Invalid` carrying the returned
+/// error.
+#[cfg(feature = "alloc")]
+#[async_trait]
+pub trait AsyncPreProcess: Send {
+	/// Perform data normalization or consistency checks; `source` is the
+	/// source the value was loaded from.
+	async fn pre_process_async(&mut self, source: &dyn Source) -> Result<(), FmtError>;
+	/// Set the context of the loaded resource; see `PreProcess::set_load_context`.
+	fn set_load_context(&mut self, _ctx: &LoadContext) {}
+}
+
+#[cfg(feature = "alloc")]
+#[async_trait]
+impl<T: TryPreProcess + Send> AsyncPreProcess for T {
+	async fn pre_process_async(&mut self, _source: &dyn Source) -> Result<(), FmtError> {
+		self.try_pre_process()
+	}
+
+	fn set_load_context(&mut self, ctx: &LoadContext) {
+		TryPreProcess::set_load_context(self, ctx);
+	}
 }
 
 /// Internal trait for optional validation.
@@ -88,7 +335,7 @@ pub trait ValidateConfig: validator::Validate {
 			#[cfg(not(feature = "std"))]
 			{
 				_ = e;
-				FmtError::Validation
+				FmtError::Validation()
 			}
 		})
 	}
@@ -115,8 +362,37 @@ pub trait Format: Send + Sync {
 	/// List of supported extensions or identifiers.
 	fn extensions(&self) -> &'static [&'static str];
 
+	/// Whether the encoding carries its own structure, which is required for
+	/// `parse_value` and for merging documents.
+	///
+	/// Defaults to `false`, so loaders only parse straight into the target
+	/// type; formats that can be parsed into a `Value` opt in.
+	fn self_describing(&self) -> bool {
+		false
+	}
+
 	/// Parse the raw bytes into the target type.
 	fn parse<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError>;
+
+	/// Parse the raw bytes into an untyped document tree.
+	///
+	/// Only self-describing formats support this; `Postcard` does not.
+	#[cfg(feature = "alloc")]
+	fn parse_value(&self, input: &[u8]) -> Result<Value, FmtError> {
+		self.parse::<Value>(input)
+	}
+
+	/// Serialize a value into the bytes of this format.
+	///
+	/// Formats without a distinct compact or pretty layout ignore `style`.
+	#[cfg(feature = "alloc")]
+	fn serialize<T: Serialize + ?Sized>(
+		&self,
+		_value: &T,
+		_style: format::Style,
+	) -> Result<Vec<u8>, FmtError> {
+		Err(FmtError::Custom("format does not support serialization"))
+	}
 }
 
 /// Abstract data source that retrieves raw bytes by key.
@@ -132,4 +408,30 @@ pub trait Source: Send + Sync {
 
 	/// Check if the resource exists at the given key.
 	async fn exists(&self, key: &str) -> bool;
+
+	/// The file backing `key`, for sources that read from the local file system.
+	///
+	/// Used to watch the resource for changes; other sources return `None`.
+	#[cfg(feature = "std")]
+	fn local_path(&self, _key: &str) -> Option<PathBuf> {
+		None
+	}
+
+	/// The directory keys are resolved against, for sources that read from
+	/// the local file system; other sources return `None`.
+	#[cfg(feature = "std")]
+	fn local_root(&self) -> Option<PathBuf> {
+		None
+	}
+}
+
+/// A source that can also store and delete resources.
+#[cfg(feature = "std")]
+#[async_trait]
+pub trait WritableSource: Source {
+	/// Write raw data, replacing any existing resource at the key.
+	async fn write(&self, key: &str, data: &[u8]) -> Result<(), FmtError>;
+
+	/// Remove the resource at the key, failing with `NotFound` if it is absent.
+	async fn remove(&self, key: &str) -> Result<(), FmtError>;
 }
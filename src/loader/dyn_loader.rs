@@ -1,34 +1,110 @@
 /* src/loader/dyn_loader.rs */
 
 #[cfg(feature = "alloc")]
-use crate::{FmtError, Format, LoadResult, PreProcess, Source, ValidateConfig, format::AnyFormat};
+use crate::{
+	AsyncPreProcess, FmtError, Format, LoadResult, Source, TryPreProcess, ValidateConfig, Value,
+	format::AnyFormat,
+	provenance::{self, Origin, OriginKind, Origins},
+	value::Coercible,
+};
 #[cfg(feature = "alloc")]
 use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 #[cfg(feature = "alloc")]
+use core::future::Future;
+#[cfg(feature = "alloc")]
+use core::pin::Pin;
+#[cfg(feature = "alloc")]
 use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use serde::ser::Serialize;
+
+#[cfg(feature = "jsonschema")]
+use super::DocumentSchema;
+use super::include;
+#[cfg(feature = "std")]
+use super::{EnvOverlay, Interpolator};
+#[cfg(feature = "std")]
+use crate::WritableSource;
+
+/// Default nesting limit for include directives.
+#[cfg(feature = "alloc")]
+const DEFAULT_INCLUDE_DEPTH: usize = 8;
 
 #[cfg(feature = "alloc")]
 pub struct DynLoader {
-	source: Box<dyn Source>,
+	source: Arc<dyn Source>,
+	/// The same source as `source`, if it is writable.
+	#[cfg(feature = "std")]
+	writer: Option<Arc<dyn WritableSource>>,
 	formats: Vec<AnyFormat>,
+	#[cfg(feature = "std")]
+	env: Option<EnvOverlay>,
+	overrides: Vec<(String, Value)>,
+	profile: Option<String>,
+	#[cfg(feature = "std")]
+	profile_env: Option<String>,
+	max_include_depth: Option<usize>,
+	#[cfg(feature = "std")]
+	interpolator: Option<Interpolator>,
+	#[cfg(feature = "jsonschema")]
+	schema: Option<DocumentSchema>,
+	deny_unknown_keys: bool,
+	accumulate_errors: bool,
+	lifecycle: super::Lifecycle,
 }
 
 #[cfg(feature = "alloc")]
 impl core::fmt::Debug for DynLoader {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-		f.debug_struct("DynLoader")
-			.field("source", &"<dyn Source>")
-			.field("formats", &self.formats)
-			.finish()
+		let mut d = f.debug_struct("DynLoader");
+		d.field("source", &"<dyn Source>");
+		#[cfg(feature = "std")]
+		d.field("writable", &self.writer.is_some());
+		d.field("formats", &self.formats);
+		#[cfg(feature = "std")]
+		d.field("env", &self.env);
+		d.field("overrides", &self.overrides)
+			.field("profile", &self.profile);
+		#[cfg(feature = "std")]
+		d.field("profile_env", &self.profile_env);
+		d.field("max_include_depth", &self.max_include_depth);
+		#[cfg(feature = "std")]
+		d.field("interpolator", &self.interpolator);
+		#[cfg(feature = "jsonschema")]
+		d.field("schema", &self.schema);
+		d.field("deny_unknown_keys", &self.deny_unknown_keys);
+		d.field("accumulate_errors", &self.accumulate_errors);
+		d.field("lifecycle", &self.lifecycle);
+		d.finish()
 	}
 }
 
 #[cfg(feature = "alloc")]
 pub struct DynLoaderBuilder {
-	source: Option<Box<dyn Source>>,
+	source: Option<Arc<dyn Source>>,
+	#[cfg(feature = "std")]
+	writer: Option<Arc<dyn WritableSource>>,
 	formats: Vec<AnyFormat>,
+	#[cfg(feature = "std")]
+	env: Option<EnvOverlay>,
+	overrides: Vec<(String, Value)>,
+	profile: Option<String>,
+	#[cfg(feature = "std")]
+	profile_env: Option<String>,
+	max_include_depth: Option<usize>,
+	#[cfg(feature = "std")]
+	interpolator: Option<Interpolator>,
+	#[cfg(feature = "jsonschema")]
+	schema: Option<DocumentSchema>,
+	deny_unknown_keys: bool,
+	accumulate_errors: bool,
+	lifecycle: super::Lifecycle,
 }
 
 #[cfg(feature = "alloc")]
@@ -36,12 +112,41 @@ impl DynLoaderBuilder {
 	pub fn new() -> Self {
 		Self {
 			source: None,
+			#[cfg(feature = "std")]
+			writer: None,
 			formats: Vec::new(),
+			#[cfg(feature = "std")]
+			env: None,
+			overrides: Vec::new(),
+			profile: None,
+			#[cfg(feature = "std")]
+			profile_env: None,
+			max_include_depth: None,
+			#[cfg(feature = "std")]
+			interpolator: None,
+			#[cfg(feature = "jsonschema")]
+			schema: None,
+			deny_unknown_keys: false,
+			accumulate_errors: false,
+			lifecycle: super::Lifecycle::new(),
 		}
 	}
 
 	pub fn source(mut self, source: impl Source + 'static) -> Self {
-		self.source = Some(Box::new(source));
+		self.source = Some(Arc::new(source));
+		#[cfg(feature = "std")]
+		{
+			self.writer = None;
+		}
+		self
+	}
+
+	/// Sets a source that `DynLoader::save` can also write to.
+	#[cfg(feature = "std")]
+	pub fn writable_source(mut self, source: impl WritableSource + 'static) -> Self {
+		let writer: Arc<dyn WritableSource> = Arc::new(source);
+		self.source = Some(writer.clone());
+		self.writer = Some(writer);
 		self
 	}
 
@@ -50,6 +155,108 @@ impl DynLoaderBuilder {
 		self
 	}
 
+	/// Overrides loaded values from environment variables.
+	#[cfg(feature = "std")]
+	pub fn env(mut self, env: EnvOverlay) -> Self {
+		self.env = Some(env);
+		self
+	}
+
+	/// Replaces the value at a dotted path (e.g. `servers[0].host`) after parsing.
+	///
+	/// String values are coerced to the type of the target field. Loading fails
+	/// with `FmtError::PathNotFound` if the path does not exist in the document.
+	pub fn with_override(mut self, path: impl Into<String>, value: impl Into<Value>) -> Self {
+		self.overrides.push((path.into(), value.into()));
+		self
+	}
+
+	/// Sets the active profile, overlaying `<base>.<profile>.<ext>` on the base file.
+	pub fn profile(mut self, profile: impl Into<String>) -> Self {
+		self.profile = Some(profile.into());
+		self
+	}
+
+	/// Reads the active profile from an environment variable at load time.
+	///
+	/// A profile set with `profile` takes precedence over the variable.
+	#[cfg(feature = "std")]
+	pub fn profile_env(mut self, var: impl Into<String>) -> Self {
+		self.profile_env = Some(var.into());
+		self
+	}
+
+	/// Resolves top-level `extends` / `include` directives.
+	///
+	/// Included keys are read through the same source, relative to the including
+	/// document, and parsed by extension with any registered format. The
+	/// including document takes precedence over its includes.
+	pub fn includes(self) -> Self {
+		self.max_include_depth(DEFAULT_INCLUDE_DEPTH)
+	}
+
+	/// Resolves include directives nested at most `depth` levels deep.
+	pub fn max_include_depth(mut self, depth: usize) -> Self {
+		self.max_include_depth = Some(depth);
+		self
+	}
+
+	/// Expands `${...}` placeholders in string values before deserialization.
+	#[cfg(feature = "std")]
+	pub fn interpolate(mut self, interpolator: Interpolator) -> Self {
+		self.interpolator = Some(interpolator);
+		self
+	}
+
+	/// Checks the merged document against a JSON Schema before deserialization.
+	///
+	/// Violations fail the load with `FmtError::Schema`.
+	#[cfg(feature = "jsonschema")]
+	pub fn schema(mut self, schema: DocumentSchema) -> Self {
+		self.schema = Some(schema);
+		self
+	}
+
+	/// Fails loading with `FmtError::UnknownKeys` when a document has keys that
+	/// match no field of the target type, instead of listing them in
+	/// `LoadInfo::unknown_keys`.
+	pub fn deny_unknown_keys(mut self) -> Self {
+		self.deny_unknown_keys = true;
+		self
+	}
+
+	/// Reports every problem of a load at once as `FmtError::Multiple`
+	/// instead of stopping at the first.
+	///
+	/// Problems are collected stage by stage: every file is parsed, then every
+	/// placeholder expanded, the schema checked, and a document that
+	/// deserializes is also validated. A stage only runs if the previous ones
+	/// succeeded, so one broken value is not reported again by later stages.
+	/// Unknown keys are listed whenever the load fails, as a misspelled key
+	/// often explains the failure.
+	pub fn accumulate_errors(mut self) -> Self {
+		self.accumulate_errors = true;
+		self
+	}
+
+	/// Does not call `set_load_context` on loaded values.
+	pub fn skip_context(mut self) -> Self {
+		self.lifecycle.context = false;
+		self
+	}
+
+	/// Does not run the pre-processing hooks on loaded values.
+	pub fn skip_pre_process(mut self) -> Self {
+		self.lifecycle.pre_process = false;
+		self
+	}
+
+	/// Returns loaded values without validating them.
+	pub fn skip_validation(mut self) -> Self {
+		self.lifecycle.validate = false;
+		self
+	}
+
 	pub fn build(self) -> Result<DynLoader, &'static str> {
 		let source = self.source.ok_or("source is required")?;
 		if self.formats.is_empty() {
@@ -57,7 +264,23 @@ impl DynLoaderBuilder {
 		}
 		Ok(DynLoader {
 			source,
+			#[cfg(feature = "std")]
+			writer: self.writer,
 			formats: self.formats,
+			#[cfg(feature = "std")]
+			env: self.env,
+			overrides: self.overrides,
+			profile: self.profile,
+			#[cfg(feature = "std")]
+			profile_env: self.profile_env,
+			max_include_depth: self.max_include_depth,
+			#[cfg(feature = "std")]
+			interpolator: self.interpolator,
+			#[cfg(feature = "jsonschema")]
+			schema: self.schema,
+			deny_unknown_keys: self.deny_unknown_keys,
+			accumulate_errors: self.accumulate_errors,
+			lifecycle: self.lifecycle,
 		})
 	}
 }
@@ -65,19 +288,129 @@ impl DynLoaderBuilder {
 #[cfg(feature = "alloc")]
 impl DynLoader {
 	pub fn new(source: Box<dyn Source>, formats: Vec<AnyFormat>) -> Self {
-		Self { source, formats }
+		Self {
+			source: Arc::from(source),
+			#[cfg(feature = "std")]
+			writer: None,
+			formats,
+			#[cfg(feature = "std")]
+			env: None,
+			overrides: Vec::new(),
+			profile: None,
+			#[cfg(feature = "std")]
+			profile_env: None,
+			max_include_depth: None,
+			#[cfg(feature = "std")]
+			interpolator: None,
+			#[cfg(feature = "jsonschema")]
+			schema: None,
+			deny_unknown_keys: false,
+			accumulate_errors: false,
+			lifecycle: super::Lifecycle::new(),
+		}
 	}
 
 	pub fn builder() -> DynLoaderBuilder {
 		DynLoaderBuilder::new()
 	}
 
+	/// The source every key is read from.
+	#[cfg(feature = "reload")]
+	pub(crate) fn source(&self) -> &dyn Source {
+		self.source.as_ref()
+	}
+
 	/// Automatically detects and loads the configuration based on registered formats.
+	///
+	/// If a profile is active and `<base_name>.<profile>.<ext>` exists, it is
+	/// overlaid on top of the base file. The value then goes through
+	/// `set_context`, `pre_process` and validation; a failed validation is
+	/// returned as `LoadResult::Invalid(FmtError::Validation(..))`.
 	pub async fn load<T>(&self, base_name: &str) -> LoadResult<T>
 	where
-		T: DeserializeOwned + PreProcess + ValidateConfig,
+		T: DeserializeOwned + TryPreProcess + ValidateConfig,
+	{
+		match self.locate(base_name).await {
+			Some((key, format, conflicts, profile)) => {
+				self
+					.load_explicit(&key, format, conflicts, profile, &super::SyncHooks)
+					.await
+			}
+			None => LoadResult::NotFound,
+		}
+	}
+
+	/// Loads `base_name` like `load`, running `AsyncPreProcess` with this
+	/// loader's source instead of `TryPreProcess`.
+	pub async fn load_with_source<T>(&self, base_name: &str) -> LoadResult<T>
+	where
+		T: DeserializeOwned + AsyncPreProcess + ValidateConfig,
 	{
-		let mut found: Option<(alloc::string::String, &AnyFormat)> = None;
+		let hooks = super::SourceHooks(self.source.as_ref());
+		match self.locate(base_name).await {
+			Some((key, format, conflicts, profile)) => {
+				self
+					.load_explicit(&key, format, conflicts, profile, &hooks)
+					.await
+			}
+			None => LoadResult::NotFound,
+		}
+	}
+
+	/// Explains where the value at a field path of `base_name` comes from.
+	///
+	/// Runs the same pipeline as `load` without deserializing the result.
+	pub async fn explain(&self, base_name: &str, path: &str) -> Result<Origin, FmtError> {
+		let (key, format, _, profile) = self.locate(base_name).await.ok_or(FmtError::NotFound)?;
+		let bytes = self.source.read(&key).await?;
+		let mut layers = Vec::new();
+		let (_, origins, _) = self
+			.build_document(
+				&key,
+				format,
+				bytes,
+				profile.as_ref(),
+				&mut layers,
+				&mut super::Failures::new(false),
+			)
+			.await?;
+		Ok(
+			provenance::lookup(&origins, path)
+				.cloned()
+				.unwrap_or_default(),
+		)
+	}
+
+	/// Finds the base file of `base_name` and, if a profile is active, its overlay.
+	async fn locate(
+		&self,
+		base_name: &str,
+	) -> Option<(String, &AnyFormat, Vec<String>, Option<Profile<'_>>)> {
+		let (key, format, mut conflicts) = self.probe(base_name).await?;
+
+		let mut profile = None;
+		if let Some(name) = self.active_profile() {
+			let profile_base = alloc::format!("{}.{}", base_name, name);
+			if let Some((profile_key, profile_format, profile_conflicts)) =
+				self.probe(&profile_base).await
+			{
+				conflicts.extend(profile_conflicts);
+				profile = Some(Profile {
+					name,
+					key: profile_key,
+					format: profile_format,
+				});
+			}
+		}
+
+		Some((key, format, conflicts, profile))
+	}
+
+	/// Finds `<base_name>.<ext>` for every registered format.
+	///
+	/// Returns the first match and any additional matches as conflicts.
+	async fn probe(&self, base_name: &str) -> Option<(String, &AnyFormat, Vec<String>)> {
+		let mut found: Option<(String, &AnyFormat)> = None;
 		let mut conflicts = Vec::new();
 
 		for format in &self.formats {
@@ -93,48 +426,132 @@ impl DynLoader {
 			}
 		}
 
-		if let Some((key, format)) = found {
-			self.load_explicit(&key, format, conflicts).await
-		} else {
-			LoadResult::NotFound
+		found.map(|(key, format)| (key, format, conflicts))
+	}
+
+	/// Every key `locate` may resolve `base_name` and its profile overlay to.
+	#[cfg(feature = "reload")]
+	pub(crate) fn candidates(&self, base_name: &str) -> Vec<String> {
+		let mut bases = alloc::vec![String::from(base_name)];
+		if let Some(name) = self.active_profile() {
+			bases.push(alloc::format!("{}.{}", base_name, name));
+		}
+
+		let mut keys = Vec::new();
+		for base in &bases {
+			for format in &self.formats {
+				for ext in format.extensions() {
+					keys.push(alloc::format!("{}.{}", base, ext));
+				}
+			}
 		}
+		keys
+	}
+
+	/// Returns the explicitly configured profile, falling back to the profile variable.
+	fn active_profile(&self) -> Option<String> {
+		if let Some(profile) = &self.profile {
+			return Some(profile.clone());
+		}
+		#[cfg(feature = "std")]
+		if let Some(var) = &self.profile_env {
+			return std::env::var(var).ok().filter(|p| !p.is_empty());
+		}
+		None
 	}
 
 	/// Directly loads a specific path, selecting parser by extension.
 	pub async fn load_file<T>(&self, path: &str) -> LoadResult<T>
 	where
-		T: DeserializeOwned + PreProcess + ValidateConfig,
+		T: DeserializeOwned + TryPreProcess + ValidateConfig,
 	{
 		let ext = if let Some(idx) = path.rfind('.') {
 			&path[idx + 1..]
 		} else {
 			#[cfg(feature = "alloc")]
-			return LoadResult::Invalid(FmtError::ParseError(alloc::string::String::from(
-				"missing extension",
-			)));
+			return LoadResult::Invalid(FmtError::parse("missing extension"));
 			#[cfg(not(feature = "alloc"))]
 			return LoadResult::Invalid(FmtError::ParseError);
 		};
 
 		for format in &self.formats {
 			if format.extensions().contains(&ext) {
-				return self.load_explicit(path, format, Vec::new()).await;
+				return self
+					.load_explicit(path, format, Vec::new(), None, &super::SyncHooks)
+					.await;
 			}
 		}
 		LoadResult::NotFound
 	}
 
+	/// Serializes `value` and writes it to `key`, selecting the format by extension.
+	///
+	/// Requires a source set with `DynLoaderBuilder::writable_source`.
+	#[cfg(feature = "std")]
+	pub async fn save<T: Serialize + ?Sized>(&self, key: &str, value: &T) -> Result<(), FmtError> {
+		let writer = self
+			.writer
+			.as_ref()
+			.ok_or(FmtError::Custom("source is not writable"))?;
+		let format = self.format_for(key).ok_or_else(|| {
+			FmtError::SerializeError(alloc::format!("no format registered for `{}`", key))
+		})?;
+		let bytes = format.serialize(value, crate::format::Style::Pretty)?;
+		writer.write(key, &bytes).await
+	}
+
+	/// Writes the JSON Schema of `T` next to the configuration at `key` (see
+	/// `schema::schema_key`) and returns the key of the schema.
+	#[cfg(feature = "schema")]
+	pub async fn save_schema<T: crate::schema::JsonSchema>(
+		&self,
+		key: &str,
+	) -> Result<String, FmtError> {
+		let writer = self
+			.writer
+			.as_ref()
+			.ok_or(FmtError::Custom("source is not writable"))?;
+		let schema_key = crate::schema::schema_key(key);
+		writer
+			.write(&schema_key, &crate::schema::to_json::<T>()?)
+			.await?;
+		Ok(schema_key)
+	}
+
+	/// Reads the TOML document at `key` for comment-preserving edits.
+	#[cfg(feature = "edit")]
+	pub async fn edit_toml(&self, key: &str) -> Result<crate::TomlEditor, FmtError> {
+		let bytes = self.source.read(key).await?;
+		crate::TomlEditor::new(key, &bytes)
+	}
+
+	/// Writes an edited document back to its key.
+	///
+	/// The edit is first deserialized and validated as `T`; nothing is
+	/// written if that fails. Returns the new value on success.
+	#[cfg(feature = "edit")]
+	pub async fn commit_toml<T>(&self, editor: &crate::TomlEditor) -> Result<T, FmtError>
+	where
+		T: DeserializeOwned + ValidateConfig,
+	{
+		let writer = self
+			.writer
+			.as_ref()
+			.ok_or(FmtError::Custom("source is not writable"))?;
+		let value = editor.check::<T>()?;
+		writer.write(editor.key(), &editor.to_bytes()).await?;
+		Ok(value)
+	}
+
 	/// Dry-run mode, validates without returning data.
 	#[cfg(feature = "validate")]
 	pub async fn validate<T>(&self, base_name: &str) -> Result<(), FmtError>
 	where
-		T: DeserializeOwned + PreProcess + validator::Validate,
+		T: DeserializeOwned + TryPreProcess + validator::Validate,
 	{
 		match self.load::<T>(base_name).await {
-			LoadResult::Ok { mut value, .. } => {
-				value.set_context(base_name);
-				value.validate_config()
-			}
+			LoadResult::Ok { value, .. } if !self.lifecycle.validate => value.validate_config(),
+			LoadResult::Ok { .. } => Ok(()),
 			LoadResult::Invalid(e) => Err(e),
 			LoadResult::NotFound => Err(FmtError::NotFound),
 		}
@@ -145,10 +562,12 @@ impl DynLoader {
 		&self,
 		key: &str,
 		format: &AnyFormat,
-		conflicts: Vec<alloc::string::String>,
+		conflicts: Vec<String>,
+		profile: Option<Profile<'_>>,
+		hooks: &impl super::Hooks<T>,
 	) -> LoadResult<T>
 	where
-		T: DeserializeOwned + PreProcess + ValidateConfig,
+		T: DeserializeOwned + ValidateConfig,
 	{
 		let bytes = match self.source.read(key).await {
 			Ok(b) => b,
@@ -156,29 +575,329 @@ impl DynLoader {
 			Err(e) => return LoadResult::Invalid(e),
 		};
 
-		match format.parse::<T>(&bytes) {
-			Ok(mut obj) => {
-				obj.pre_process();
-
-				LoadResult::Ok {
-					value: obj,
-					info: crate::LoadInfo {
-						#[cfg(feature = "std")]
-						path: std::path::PathBuf::from(key),
-						#[cfg(not(feature = "std"))]
-						key: alloc::string::String::from(key),
-						format: format.extensions().first().copied().unwrap_or("unknown"),
-						#[cfg(feature = "std")]
-						conflicts: conflicts
-							.into_iter()
-							.map(std::path::PathBuf::from)
-							.collect(),
-						#[cfg(not(feature = "std"))]
-						conflicts,
-					},
+		let mut failures = super::Failures::new(self.accumulate_errors);
+		let mut layers = Vec::new();
+		let mut origins = Origins::new();
+		let mut unknown_keys = Vec::new();
+		let parsed = if profile.is_none() && !self.transforms_document() && !self.has_schema() {
+			layers.push(String::from(key));
+			let doc = if format.self_describing() {
+				format.parse_value(&bytes).ok()
+			} else {
+				None
+			};
+			if let Some(doc) = &doc {
+				let template = Origin {
+					format: format.extensions().first().copied(),
+					..Origin::new(OriginKind::File, key)
+				};
+				let positions = format.positions(&bytes);
+				provenance::record_document(&mut origins, doc, &template, &positions);
+				// Unknown keys are pointed at in the file
+				if !positions.is_empty() {
+					failures.keep_source(key, bytes.clone(), positions);
 				}
 			}
-			Err(e) => LoadResult::Invalid(e),
+			super::parse_direct::<T, _>(
+				format,
+				key,
+				&bytes,
+				doc,
+				&origins,
+				self.deny_unknown_keys,
+				&mut failures,
+			)
+			.map(|parsed| {
+				parsed.map(|(value, unknown)| {
+					unknown_keys = unknown;
+					value
+				})
+			})
+		} else if format.self_describing() {
+			self
+				.build_document(
+					key,
+					format,
+					bytes,
+					profile.as_ref(),
+					&mut layers,
+					&mut failures,
+				)
+				.await
+				.and_then(|(doc, doc_origins, coercible)| {
+					origins = doc_origins;
+					if !failures.is_empty() {
+						return Ok(None);
+					}
+					if let Err(e) = self.check_schema(&doc, &coercible) {
+						failures.recover(e)?;
+						return Ok(None);
+					}
+					let parsed = super::deserialize_document::<T>(
+						doc,
+						&origins,
+						&coercible,
+						self.deny_unknown_keys,
+						&mut failures,
+					)?;
+					Ok(parsed.map(|(value, unknown)| {
+						unknown_keys = unknown;
+						value
+					}))
+				})
+		} else if profile.is_some() || self.transforms_document() {
+			Err(FmtError::parse(
+				"format is not self-describing and cannot be merged or overridden",
+			))
+		} else {
+			Err(FmtError::parse(
+				"format is not self-describing and cannot be checked against a schema",
+			))
+		};
+
+		let mut info = crate::LoadInfo::new(
+			key,
+			format.extensions().first().copied().unwrap_or("unknown"),
+			conflicts,
+		);
+		info.layers.clear();
+		for layer in &layers {
+			info.push_layer(layer);
 		}
+		info.profile = profile.map(|p| p.name);
+		info.origins = origins;
+		info.unknown_keys = unknown_keys;
+
+		let context = crate::LoadContext::new(&info, self.source.as_ref());
+		let obj = match super::complete(parsed, failures, &info, &context, hooks, self.lifecycle).await
+		{
+			Ok(obj) => obj,
+			Err(e) => return LoadResult::Invalid(e),
+		};
+
+		LoadResult::Ok { value: obj, info }
 	}
+
+	/// Builds the merged document: the base file and its includes, the profile
+	/// overlay and its includes, placeholder expansion, then environment and
+	/// programmatic overrides.
+	///
+	/// Also returns the paths of the values inserted as text, which are
+	/// coerced to the field type. In accumulate mode, files that fail to parse
+	/// are recorded and skipped, and the document is returned without
+	/// expansion or overrides.
+	async fn build_document(
+		&self,
+		key: &str,
+		format: &AnyFormat,
+		bytes: Vec<u8>,
+		profile: Option<&Profile<'_>>,
+		layers: &mut Vec<String>,
+		failures: &mut super::Failures,
+	) -> Result<(Value, Origins, Coercible), FmtError> {
+		let (mut doc, mut origins) = match self
+			.resolve(key, format, bytes, &mut Vec::new(), layers, failures)
+			.await
+		{
+			Ok(resolved) => resolved,
+			Err(e) => {
+				failures.recover(e)?;
+				(Value::Table(Default::default()), Origins::new())
+			}
+		};
+
+		if let Some(profile) = profile {
+			let overlay = match self.source.read(&profile.key).await {
+				Ok(bytes) => {
+					self
+						.resolve(
+							&profile.key,
+							profile.format,
+							bytes,
+							&mut Vec::new(),
+							layers,
+							failures,
+						)
+						.await
+				}
+				Err(e) => Err(e),
+			};
+			match overlay {
+				Ok((overlay, overlay_origins)) => {
+					doc.merge(overlay);
+					provenance::overlay(&mut origins, overlay_origins);
+				}
+				Err(e) => failures.recover(e)?,
+			}
+		}
+		let mut coercible = Coercible::new();
+		if !failures.is_empty() {
+			return Ok((doc, origins, coercible));
+		}
+
+		#[cfg(feature = "std")]
+		if let Some(interpolator) = &self.interpolator {
+			for e in interpolator.apply(&mut doc, &origins, &mut coercible) {
+				failures.recover(e)?;
+			}
+		}
+		#[cfg(feature = "std")]
+		if let Some(env) = &self.env {
+			env.apply(&mut doc, &mut origins, &mut coercible);
+		}
+		for (path, value) in &self.overrides {
+			match doc.set_path(path, value.clone()) {
+				Ok(()) => {
+					provenance::set(&mut origins, path, Origin::new(OriginKind::Override, path));
+					coercible.insert(path.clone());
+				}
+				Err(e) => failures.recover(e)?,
+			}
+		}
+		Ok((doc, origins, coercible))
+	}
+
+	/// Parses a document and, if enabled, merges it on top of its includes.
+	///
+	/// `stack` holds the chain of including documents for cycle detection, and
+	/// every resolved key is appended to `layers` in precedence order.
+	fn resolve<'a>(
+		&'a self,
+		key: &'a str,
+		format: &'a AnyFormat,
+		bytes: Vec<u8>,
+		stack: &'a mut Vec<String>,
+		layers: &'a mut Vec<String>,
+		failures: &'a mut super::Failures,
+	) -> Resolving<'a> {
+		Box::pin(async move {
+			let mut doc = format.parse_value(&bytes).map_err(|e| e.in_key(key))?;
+
+			let includes = match self.max_include_depth {
+				Some(_) => include::take_includes(&mut doc)?,
+				None => Vec::new(),
+			};
+
+			let mut origins = Origins::new();
+			let template = Origin {
+				format: format.extensions().first().copied(),
+				..Origin::new(OriginKind::File, key)
+			};
+			let positions = format.positions(&bytes);
+			provenance::record_document(&mut origins, &doc, &template, &positions);
+			failures.keep_source(key, bytes, positions);
+
+			if includes.is_empty() {
+				layers.push(String::from(key));
+				return Ok((doc, origins));
+			}
+			let max_depth = self.max_include_depth.unwrap_or_default();
+			if stack.len() >= max_depth {
+				return Err(FmtError::IncludeDepth(max_depth));
+			}
+
+			stack.push(String::from(key));
+			let mut merged = Value::Table(Default::default());
+			let mut merged_origins = Origins::new();
+			for include in includes {
+				let included_key = match include::resolve_key(key, &include) {
+					Ok(included_key) => included_key,
+					Err(e) => {
+						failures.recover(e)?;
+						continue;
+					}
+				};
+				if stack.contains(&included_key) {
+					let mut chain = stack.join(" -> ");
+					chain.push_str(" -> ");
+					chain.push_str(&included_key);
+					failures.recover(FmtError::IncludeCycle(chain))?;
+					continue;
+				}
+				let Some(included_format) = self.format_for(&included_key) else {
+					failures.recover(FmtError::parse(alloc::format!(
+						"no format registered for '{}'",
+						included_key
+					)))?;
+					continue;
+				};
+				let included = match self.source.read(&included_key).await {
+					Ok(included_bytes) => {
+						self
+							.resolve(
+								&included_key,
+								included_format,
+								included_bytes,
+								stack,
+								layers,
+								failures,
+							)
+							.await
+					}
+					Err(e) => Err(e),
+				};
+				match included {
+					Ok((included, included_origins)) => {
+						merged.merge(included);
+						provenance::overlay(&mut merged_origins, included_origins);
+					}
+					Err(e) => failures.recover(e)?,
+				}
+			}
+			stack.pop();
+
+			merged.merge(doc);
+			provenance::overlay(&mut merged_origins, origins);
+			layers.push(String::from(key));
+			Ok((merged, merged_origins))
+		})
+	}
+
+	/// Selects the registered format matching the extension of `key`.
+	fn format_for(&self, key: &str) -> Option<&AnyFormat> {
+		let ext = &key[key.rfind('.')? + 1..];
+		self
+			.formats
+			.iter()
+			.find(|format| format.extensions().contains(&ext))
+	}
+
+	/// Checks the merged document against the schema, if one is set, with
+	/// strings from the environment and overrides coerced to its types.
+	fn check_schema(&self, _doc: &Value, _coercible: &Coercible) -> Result<(), FmtError> {
+		#[cfg(feature = "jsonschema")]
+		if let Some(schema) = &self.schema {
+			schema.check_coerced(_doc, _coercible)?;
+		}
+		Ok(())
+	}
+
+	fn has_schema(&self) -> bool {
+		#[cfg(feature = "jsonschema")]
+		if self.schema.is_some() {
+			return true;
+		}
+		false
+	}
+
+	/// Whether any stage modifies the parsed document before deserialization.
+	fn transforms_document(&self) -> bool {
+		#[cfg(feature = "std")]
+		if self.env.is_some() || self.interpolator.is_some() {
+			return true;
+		}
+		!self.overrides.is_empty() || self.max_include_depth.is_some()
+	}
+}
+
+/// A document being resolved together with its includes.
+#[cfg(feature = "alloc")]
+type Resolving<'a> = Pin<Box<dyn Future<Output = Result<(Value, Origins), FmtError>> + Send + 'a>>;
+
+/// The active profile and the overlay file found for it.
+#[cfg(feature = "alloc")]
+struct Profile<'a> {
+	name: String,
+	key: String,
+	format: &'a AnyFormat,
 }
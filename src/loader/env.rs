@@ -0,0 +1,120 @@
+/* src/loader/env.rs */
+
+use crate::provenance::{self, Origin, OriginKind, Origins};
+use crate::value::{Coercible, Map, Value};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Overrides document values from environment variables.
+///
+/// With the prefix `APP` and the default separator `__`, the variable
+/// `APP__SERVER__PORT=8080` overrides `server.port`. Values are inserted as
+/// strings and coerced to the type of the target field during deserialization,
+/// so numbers, booleans and comma-separated lists are supported.
+#[derive(Debug, Clone)]
+pub struct EnvOverlay {
+	prefix: String,
+	separator: String,
+	lowercase: bool,
+	vars: Option<Vec<(String, String)>>,
+}
+
+impl EnvOverlay {
+	/// Creates an overlay for variables starting with `prefix` and the default `__` separator.
+	pub fn new(prefix: impl Into<String>) -> Self {
+		Self {
+			prefix: prefix.into(),
+			separator: String::from("__"),
+			lowercase: true,
+			vars: None,
+		}
+	}
+
+	/// Sets the separator between the prefix and nested key segments.
+	pub fn separator(mut self, separator: impl Into<String>) -> Self {
+		self.separator = separator.into();
+		self
+	}
+
+	/// Keeps key segments as written instead of folding them to lowercase.
+	pub fn case_sensitive(mut self) -> Self {
+		self.lowercase = false;
+		self
+	}
+
+	/// Uses a fixed set of variables instead of the process environment.
+	pub fn vars<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+	where
+		K: Into<String>,
+		V: Into<String>,
+	{
+		self.vars = Some(
+			vars
+				.into_iter()
+				.map(|(k, v)| (k.into(), v.into()))
+				.collect(),
+		);
+		self
+	}
+
+	/// Returns the matching variables as name, key segments and raw value, sorted by name.
+	///
+	/// Process variables whose name or value is not valid UTF-8 are skipped.
+	pub(crate) fn collect(&self) -> Vec<(String, Vec<String>, String)> {
+		let mut vars: Vec<(String, String)> = match &self.vars {
+			Some(vars) => vars.clone(),
+			None => std::env::vars_os()
+				.filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+				.collect(),
+		};
+		vars.sort();
+
+		let head = alloc::format!("{}{}", self.prefix, self.separator);
+		vars
+			.into_iter()
+			.filter_map(|(name, value)| {
+				let rest = name.strip_prefix(&head)?;
+				let segments: Vec<String> = rest
+					.split(self.separator.as_str())
+					.map(|s| {
+						if self.lowercase {
+							s.to_lowercase()
+						} else {
+							String::from(s)
+						}
+					})
+					.collect();
+				if segments.iter().any(String::is_empty) {
+					return None;
+				}
+				Some((name, segments, value))
+			})
+			.collect()
+	}
+
+	/// Applies every matching variable on top of the document, marking its
+	/// path as coercible.
+	pub(crate) fn apply(&self, doc: &mut Value, origins: &mut Origins, coercible: &mut Coercible) {
+		for (name, segments, value) in self.collect() {
+			insert(doc, &segments, Value::String(value));
+			let path = segments.join(".");
+			provenance::set(origins, &path, Origin::new(OriginKind::Env, &name));
+			coercible.insert(path);
+		}
+	}
+}
+
+/// Inserts `value` at the nested key, creating intermediate tables as needed.
+fn insert(doc: &mut Value, segments: &[String], value: Value) {
+	let mut current = doc;
+	for segment in segments {
+		if !matches!(current, Value::Table(_)) {
+			*current = Value::Table(Map::new());
+		}
+		let Value::Table(table) = current else {
+			unreachable!()
+		};
+		current = table.entry(segment.clone()).or_insert(Value::Null);
+	}
+	*current = value;
+}
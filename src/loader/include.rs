@@ -0,0 +1,81 @@
+/* src/loader/include.rs */
+
+use crate::{FmtError, Value};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Top-level keys that pull other documents into the current one.
+const DIRECTIVES: &[&str] = &["extends", "include"];
+
+/// Removes the include directives from a document and returns the referenced keys.
+///
+/// `extends` entries come before `include` entries. Each directive accepts a
+/// single string or an array of strings.
+pub(crate) fn take_includes(doc: &mut Value) -> Result<Vec<String>, FmtError> {
+	let Value::Table(table) = doc else {
+		return Ok(Vec::new());
+	};
+
+	let mut includes = Vec::new();
+	for directive in DIRECTIVES {
+		match table.remove(*directive) {
+			None => {}
+			Some(Value::String(key)) => includes.push(key),
+			Some(Value::Array(items)) => {
+				for item in items {
+					match item {
+						Value::String(key) => includes.push(key),
+						other => return Err(invalid_directive(directive, &other)),
+					}
+				}
+			}
+			Some(other) => return Err(invalid_directive(directive, &other)),
+		}
+	}
+	Ok(includes)
+}
+
+fn invalid_directive(directive: &str, value: &Value) -> FmtError {
+	FmtError::parse(alloc::format!(
+		"`{}` must be a string or an array of strings, found {}",
+		directive,
+		value.kind()
+	))
+}
+
+/// Resolves `include` relative to the directory of `from`.
+///
+/// `.` and `..` segments are collapsed lexically. Segments that would climb
+/// above the source root are kept, so the source can reject them (e.g.
+/// `FileSource` reports a sandbox violation). Absolute includes fail with
+/// `FmtError::InvalidPath`, whatever the source.
+pub(crate) fn resolve_key(from: &str, include: &str) -> Result<String, FmtError> {
+	if include.starts_with(['/', '\\']) {
+		return Err(FmtError::InvalidPath(alloc::format!(
+			"`{}` included by {} is absolute, includes are relative to the including file",
+			include,
+			from
+		)));
+	}
+
+	let mut segments: Vec<&str> = from
+		.split('/')
+		.filter(|s| !s.is_empty() && *s != ".")
+		.collect();
+	// Drop the file name of the including document.
+	segments.pop();
+
+	for segment in include.split('/') {
+		match segment {
+			"" | "." => {}
+			".." => match segments.last() {
+				Some(last) if *last != ".." => {
+					segments.pop();
+				}
+				_ => segments.push(".."),
+			},
+			other => segments.push(other),
+		}
+	}
+	Ok(segments.join("/"))
+}
@@ -0,0 +1,199 @@
+/* src/loader/interpolate.rs */
+
+use crate::provenance::{self, Origins};
+use crate::value::Coercible;
+use crate::{FmtError, Value};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Expands `${...}` placeholders in string values before deserialization.
+///
+/// - `${VAR}` resolves a key of the same document (e.g. `${server.host}`),
+///   falling back to the environment variable `VAR`.
+/// - `${env:VAR}` always reads the environment variable.
+/// - `${VAR:-default}` uses `default` when the lookup fails.
+/// - `$${` produces a literal `${`.
+///
+/// Expanded strings are coerced to the type of the target field, so
+/// `port = "${PORT}"` loads into an integer field.
+#[derive(Debug, Clone, Default)]
+pub struct Interpolator {
+	vars: Option<BTreeMap<String, String>>,
+}
+
+impl Interpolator {
+	/// Creates an interpolator reading the process environment.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Uses a fixed set of variables instead of the process environment.
+	pub fn vars<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+	where
+		K: Into<String>,
+		V: Into<String>,
+	{
+		self.vars = Some(
+			vars
+				.into_iter()
+				.map(|(k, v)| (k.into(), v.into()))
+				.collect(),
+		);
+		self
+	}
+
+	/// Expands every string value of the document, returning one error per
+	/// placeholder that failed to expand.
+	///
+	/// `origins` names the file of a failing value in errors. Expanded values
+	/// are marked as coercible.
+	pub(crate) fn apply(
+		&self,
+		doc: &mut Value,
+		origins: &Origins,
+		coercible: &mut Coercible,
+	) -> Vec<FmtError> {
+		let snapshot = doc.clone();
+		let mut path = String::new();
+		let mut errors = Vec::new();
+		self.walk(doc, &snapshot, &mut path, origins, coercible, &mut errors);
+		errors
+	}
+
+	fn walk(
+		&self,
+		value: &mut Value,
+		root: &Value,
+		path: &mut String,
+		origins: &Origins,
+		coercible: &mut Coercible,
+		errors: &mut Vec<FmtError>,
+	) {
+		match value {
+			Value::String(s) if s.contains('$') => {
+				let mut stack = alloc::vec![path.clone()];
+				match self.expand(s, root, &mut stack) {
+					Ok(expanded) => {
+						*s = expanded;
+						coercible.insert(path.clone());
+					}
+					Err(message) => errors.push(FmtError::Interpolation {
+						file: provenance::lookup(origins, path)
+							.map(|origin| origin.source.clone())
+							.unwrap_or_default(),
+						key: path.clone(),
+						message,
+					}),
+				}
+			}
+			Value::Array(items) => {
+				for (i, item) in items.iter_mut().enumerate() {
+					let len = path.len();
+					path.push_str(&alloc::format!("[{}]", i));
+					self.walk(item, root, path, origins, coercible, errors);
+					path.truncate(len);
+				}
+			}
+			Value::Table(table) => {
+				for (key, item) in table.iter_mut() {
+					let len = path.len();
+					if !path.is_empty() {
+						path.push('.');
+					}
+					path.push_str(key);
+					self.walk(item, root, path, origins, coercible, errors);
+					path.truncate(len);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Expands the placeholders of a single string.
+	///
+	/// `stack` holds the document keys currently being expanded, to detect
+	/// self-referencing placeholders.
+	fn expand(&self, input: &str, root: &Value, stack: &mut Vec<String>) -> Result<String, String> {
+		let mut out = String::with_capacity(input.len());
+		let mut rest = input;
+
+		while let Some(idx) = rest.find('$') {
+			out.push_str(&rest[..idx]);
+			rest = &rest[idx..];
+
+			if let Some(tail) = rest.strip_prefix("$${") {
+				out.push_str("${");
+				rest = tail;
+			} else if let Some(tail) = rest.strip_prefix("${") {
+				let end = tail
+					.find('}')
+					.ok_or_else(|| String::from("unterminated placeholder"))?;
+				out.push_str(&self.lookup(&tail[..end], root, stack)?);
+				rest = &tail[end + 1..];
+			} else {
+				out.push('$');
+				rest = &rest[1..];
+			}
+		}
+
+		out.push_str(rest);
+		Ok(out)
+	}
+
+	fn lookup(&self, expr: &str, root: &Value, stack: &mut Vec<String>) -> Result<String, String> {
+		let (name, default) = match expr.split_once(":-") {
+			Some((name, default)) => (name.trim(), Some(default)),
+			None => (expr.trim(), None),
+		};
+
+		let found = match name.strip_prefix("env:") {
+			Some(var) => self.var(var),
+			None => match root.get_path(name) {
+				Some(value) => Some(self.reference(name, value, root, stack)?),
+				None => self.var(name),
+			},
+		};
+
+		found
+			.or_else(|| default.map(String::from))
+			.ok_or_else(|| alloc::format!("`{}` is not defined", name))
+	}
+
+	fn reference(
+		&self,
+		name: &str,
+		value: &Value,
+		root: &Value,
+		stack: &mut Vec<String>,
+	) -> Result<String, String> {
+		match value {
+			Value::String(s) => {
+				if stack.iter().any(|k| k == name) {
+					return Err(alloc::format!("`{}` references itself", name));
+				}
+				stack.push(String::from(name));
+				let expanded = self.expand(s, root, stack)?;
+				stack.pop();
+				Ok(expanded)
+			}
+			Value::Datetime(v) => Ok(v.clone()),
+			Value::Bool(v) => Ok(v.to_string()),
+			Value::Integer(v) => Ok(v.to_string()),
+			Value::UInteger(v) => Ok(v.to_string()),
+			Value::Float(v) => Ok(v.to_string()),
+			other => Err(alloc::format!(
+				"`{}` is a {} and cannot be interpolated",
+				name,
+				other.kind()
+			)),
+		}
+	}
+
+	fn var(&self, name: &str) -> Option<String> {
+		match &self.vars {
+			Some(vars) => vars.get(name).cloned(),
+			None => std::env::var(name).ok(),
+		}
+	}
+}
@@ -0,0 +1,262 @@
+/* src/loader/layered.rs */
+
+use crate::{
+	FmtError, Format, LoadInfo, LoadResult, Source, TryPreProcess, ValidateConfig, Value,
+	format::AnyFormat,
+	provenance::{self, Origin, OriginKind, Origins},
+	value::Coercible,
+};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+
+/// A single layer of a layered configuration.
+pub struct Layer {
+	source: Box<dyn Source>,
+	key: String,
+	format: AnyFormat,
+	required: bool,
+}
+
+impl core::fmt::Debug for Layer {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Layer")
+			.field("source", &"<dyn Source>")
+			.field("key", &self.key)
+			.field("format", &self.format)
+			.field("required", &self.required)
+			.finish()
+	}
+}
+
+impl Layer {
+	/// Creates an optional layer reading `key` from `source` with the given format.
+	pub fn new(source: impl Source + 'static, key: impl Into<String>, format: AnyFormat) -> Self {
+		Self {
+			source: Box::new(source),
+			key: key.into(),
+			format,
+			required: false,
+		}
+	}
+
+	/// Marks the layer as required; loading fails with `NotFound` if it is missing.
+	pub fn required(mut self) -> Self {
+		self.required = true;
+		self
+	}
+}
+
+/// Loads several layers and deep-merges them before deserializing.
+///
+/// Layers are applied in registration order, so later layers take precedence
+/// over earlier ones (e.g. defaults, system file, user file, local override).
+#[derive(Debug)]
+pub struct LayeredLoader {
+	layers: Vec<Layer>,
+	deny_unknown_keys: bool,
+	accumulate_errors: bool,
+	lifecycle: super::Lifecycle,
+}
+
+pub struct LayeredLoaderBuilder {
+	layers: Vec<Layer>,
+	deny_unknown_keys: bool,
+	accumulate_errors: bool,
+	lifecycle: super::Lifecycle,
+}
+
+impl LayeredLoaderBuilder {
+	pub fn new() -> Self {
+		Self {
+			layers: Vec::new(),
+			deny_unknown_keys: false,
+			accumulate_errors: false,
+			lifecycle: super::Lifecycle::new(),
+		}
+	}
+
+	/// Appends a layer with higher precedence than all previous layers.
+	pub fn layer(mut self, layer: Layer) -> Self {
+		self.layers.push(layer);
+		self
+	}
+
+	/// Fails loading with `FmtError::UnknownKeys` when the merged document has
+	/// keys that match no field of the target type, instead of listing them in
+	/// `LoadInfo::unknown_keys`.
+	pub fn deny_unknown_keys(mut self) -> Self {
+		self.deny_unknown_keys = true;
+		self
+	}
+
+	/// Reports every problem of a load at once as `FmtError::Multiple`
+	/// instead of stopping at the first.
+	///
+	/// Every layer is parsed even if an earlier one fails; the merged document
+	/// is only deserialized and validated if all of them parse.
+	pub fn accumulate_errors(mut self) -> Self {
+		self.accumulate_errors = true;
+		self
+	}
+
+	/// Does not call `set_load_context` on loaded values.
+	pub fn skip_context(mut self) -> Self {
+		self.lifecycle.context = false;
+		self
+	}
+
+	/// Does not run the pre-processing hooks on loaded values.
+	pub fn skip_pre_process(mut self) -> Self {
+		self.lifecycle.pre_process = false;
+		self
+	}
+
+	/// Returns loaded values without validating them.
+	pub fn skip_validation(mut self) -> Self {
+		self.lifecycle.validate = false;
+		self
+	}
+
+	pub fn build(self) -> Result<LayeredLoader, &'static str> {
+		if self.layers.is_empty() {
+			return Err("at least one layer is required");
+		}
+		Ok(LayeredLoader {
+			layers: self.layers,
+			deny_unknown_keys: self.deny_unknown_keys,
+			accumulate_errors: self.accumulate_errors,
+			lifecycle: self.lifecycle,
+		})
+	}
+}
+
+impl LayeredLoader {
+	pub fn new(layers: Vec<Layer>) -> Self {
+		Self {
+			layers,
+			deny_unknown_keys: false,
+			accumulate_errors: false,
+			lifecycle: super::Lifecycle::new(),
+		}
+	}
+
+	pub fn builder() -> LayeredLoaderBuilder {
+		LayeredLoaderBuilder::new()
+	}
+
+	/// Reads and merges every layer, then deserializes the result and runs
+	/// `set_load_context` with the last layer, `pre_process` and
+	/// validation on it.
+	///
+	/// Missing optional layers are skipped. If no layer exists, or a required
+	/// layer is missing, `LoadResult::NotFound` is returned.
+	pub async fn load<T>(&self) -> LoadResult<T>
+	where
+		T: DeserializeOwned + TryPreProcess + ValidateConfig,
+	{
+		let mut merged: Option<Value> = None;
+		let mut info: Option<LoadInfo> = None;
+		let mut last: Option<&Layer> = None;
+		let mut origins = Origins::new();
+		let mut failures = super::Failures::new(self.accumulate_errors);
+
+		for layer in &self.layers {
+			let bytes = match layer.source.read(&layer.key).await {
+				Ok(b) => b,
+				Err(FmtError::NotFound) if !layer.required => continue,
+				Err(FmtError::NotFound) => return LoadResult::NotFound,
+				Err(e) => match failures.recover(e) {
+					Ok(()) => continue,
+					Err(e) => return LoadResult::Invalid(e),
+				},
+			};
+
+			let value = match layer.format.parse_value(&bytes) {
+				Ok(v) => v,
+				Err(e) => match failures.recover(e.in_key(&layer.key)) {
+					Ok(()) => continue,
+					Err(e) => return LoadResult::Invalid(e),
+				},
+			};
+
+			let format = layer
+				.format
+				.extensions()
+				.first()
+				.copied()
+				.unwrap_or("unknown");
+
+			let mut layer_origins = Origins::new();
+			let template = Origin {
+				format: Some(format),
+				..Origin::new(OriginKind::File, &layer.key)
+			};
+			let positions = layer.format.positions(&bytes);
+			provenance::record_document(&mut layer_origins, &value, &template, &positions);
+			failures.keep_source(&layer.key, bytes, positions);
+			provenance::overlay(&mut origins, layer_origins);
+
+			match merged.as_mut() {
+				Some(base) => base.merge(value),
+				None => merged = Some(value),
+			}
+
+			let current = LoadInfo::new(&layer.key, format, Vec::new());
+			last = Some(layer);
+			info = Some(match info {
+				Some(mut prev) => {
+					prev.layers.extend(current.layers);
+					LoadInfo {
+						layers: prev.layers,
+						..current
+					}
+				}
+				None => current,
+			});
+		}
+
+		if !failures.is_empty() {
+			return LoadResult::Invalid(
+				failures
+					.finish(&origins, &[])
+					.expect_err("a failure was recorded"),
+			);
+		}
+		let (Some(merged), Some(mut info), Some(last)) = (merged, info, last) else {
+			return LoadResult::NotFound;
+		};
+		info.origins = origins;
+
+		let mut unknown_keys = Vec::new();
+		let parsed = super::deserialize_document::<T>(
+			merged,
+			&info.origins,
+			&Coercible::new(),
+			self.deny_unknown_keys,
+			&mut failures,
+		)
+		.map(|parsed| {
+			parsed.map(|(value, unknown)| {
+				unknown_keys = unknown;
+				value
+			})
+		});
+		info.unknown_keys = unknown_keys;
+		let context = crate::LoadContext::new(&info, last.source.as_ref());
+		match super::complete(
+			parsed,
+			failures,
+			&info,
+			&context,
+			&super::SyncHooks,
+			self.lifecycle,
+		)
+		.await
+		{
+			Ok(obj) => LoadResult::Ok { value: obj, info },
+			Err(e) => LoadResult::Invalid(e),
+		}
+	}
+}
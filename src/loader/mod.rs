@@ -1,5 +1,3 @@
-/* src/loader/mod.rs */
-
 mod static_loader;
 pub use static_loader::StaticLoader;
 
@@ -7,3 +5,298 @@ pub use static_loader::StaticLoader;
 mod dyn_loader;
 #[cfg(feature = "alloc")]
 pub use dyn_loader::DynLoader;
+
+#[cfg(feature = "alloc")]
+mod include;
+
+#[cfg(feature = "alloc")]
+use crate::{
+	AsyncPreProcess, FmtError, FmtErrors, Format, LoadContext, LoadInfo, Source, TryPreProcess,
+	UnknownKey, ValidateConfig, Value,
+	provenance::{self, Origins, Positions, SourceFile, Sources},
+	value::Coercible,
+};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Where a load reports recoverable failures: returned at once, or collected
+/// in accumulate mode so that the load can go on and find more.
+///
+/// It also keeps the bytes of the parsed files that report positions, so
+/// that errors located in them can show the offending line.
+#[cfg(feature = "alloc")]
+pub(crate) struct Failures {
+	errors: Option<FmtErrors>,
+	sources: Sources,
+}
+
+#[cfg(feature = "alloc")]
+impl Failures {
+	pub(crate) fn new(accumulate: bool) -> Self {
+		Self {
+			errors: accumulate.then(FmtErrors::new),
+			sources: Sources::new(),
+		}
+	}
+
+	/// Keeps the bytes of `key` if its values carry positions.
+	pub(crate) fn keep_source(&mut self, key: &str, bytes: Vec<u8>, positions: Positions) {
+		if !positions.is_empty() {
+			self
+				.sources
+				.insert(String::from(key), SourceFile { bytes, positions });
+		}
+	}
+
+	/// An `UnknownKeys` error, carrying the files the keys point into for
+	/// rendering diagnostics.
+	fn unknown_keys(&self, keys: Vec<UnknownKey>) -> FmtError {
+		#[cfg(feature = "diagnostics")]
+		let keys = keys
+			.into_iter()
+			.map(|mut key| {
+				let file = key.file.as_ref().and_then(|file| self.sources.get(file));
+				if let (Some(_), Some(file)) = (&key.span, file) {
+					key.source = Some(String::from_utf8_lossy(&file.bytes).into_owned());
+				}
+				key
+			})
+			.collect();
+		FmtError::UnknownKeys(keys)
+	}
+
+	/// Records `error` in accumulate mode; otherwise returns it.
+	pub(crate) fn recover(&mut self, error: FmtError) -> Result<(), FmtError> {
+		match &mut self.errors {
+			Some(errors) => {
+				errors.push(error);
+				Ok(())
+			}
+			None => Err(error),
+		}
+	}
+
+	pub(crate) fn is_empty(&self) -> bool {
+		self.errors.as_ref().is_none_or(FmtErrors::is_empty)
+	}
+
+	/// Fails with every recorded problem, located through `origins`.
+	///
+	/// `unknown` are the keys that did not fail the load by themselves; they
+	/// are reported too, since a misspelled key often explains a failure.
+	pub(crate) fn finish(self, origins: &Origins, unknown: &[UnknownKey]) -> Result<(), FmtError> {
+		let unknown =
+			(!unknown.is_empty() && !self.is_empty()).then(|| self.unknown_keys(unknown.to_vec()));
+		match self.errors {
+			Some(mut errors) if !errors.is_empty() => {
+				if let Some(unknown) = unknown {
+					errors.push(unknown);
+				}
+				errors.locate(origins);
+				Err(FmtError::Multiple(errors))
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+/// The stages a loader runs on a deserialized value, in order:
+/// `set_load_context`, `pre_process`, then validation. Each can be skipped.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Lifecycle {
+	pub(crate) context: bool,
+	pub(crate) pre_process: bool,
+	pub(crate) validate: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl Lifecycle {
+	/// Every stage enabled.
+	pub(crate) const fn new() -> Self {
+		Self {
+			context: true,
+			pre_process: true,
+			validate: true,
+		}
+	}
+}
+
+/// The pre-processing hooks a load runs on `T`.
+#[cfg(feature = "alloc")]
+pub(crate) trait Hooks<T> {
+	fn set_load_context(&self, value: &mut T, context: &LoadContext);
+	async fn pre_process(&self, value: &mut T) -> Result<(), FmtError>;
+}
+
+/// Runs `TryPreProcess`, which `load` asks for.
+#[cfg(feature = "alloc")]
+pub(crate) struct SyncHooks;
+
+#[cfg(feature = "alloc")]
+impl<T: TryPreProcess> Hooks<T> for SyncHooks {
+	fn set_load_context(&self, value: &mut T, context: &LoadContext) {
+		TryPreProcess::set_load_context(value, context);
+	}
+
+	async fn pre_process(&self, value: &mut T) -> Result<(), FmtError> {
+		value.try_pre_process()
+	}
+}
+
+/// Runs `AsyncPreProcess` with the source the value was loaded from.
+#[cfg(feature = "alloc")]
+pub(crate) struct SourceHooks<'a>(pub(crate) &'a dyn Source);
+
+#[cfg(feature = "alloc")]
+impl<T: AsyncPreProcess> Hooks<T> for SourceHooks<'_> {
+	fn set_load_context(&self, value: &mut T, context: &LoadContext) {
+		AsyncPreProcess::set_load_context(value, context);
+	}
+
+	async fn pre_process(&self, value: &mut T) -> Result<(), FmtError> {
+		value.pre_process_async(self.0).await
+	}
+}
+
+/// Runs the lifecycle stages on a deserialized value, then fails with every
+/// recorded problem.
+///
+/// `parsed` is `None` if deserialization already recorded a failure. A
+/// failed pre-processing hook ends the load without validating the value.
+#[cfg(feature = "alloc")]
+pub(crate) async fn complete<T: ValidateConfig>(
+	parsed: Result<Option<T>, FmtError>,
+	mut failures: Failures,
+	info: &LoadInfo,
+	context: &LoadContext,
+	hooks: &impl Hooks<T>,
+	lifecycle: Lifecycle,
+) -> Result<T, FmtError> {
+	let Some(mut value) = parsed? else {
+		return Err(
+			failures
+				.finish(&info.origins, &info.unknown_keys)
+				.expect_err("a failure was recorded"),
+		);
+	};
+	if lifecycle.context {
+		hooks.set_load_context(&mut value, context);
+	}
+	if lifecycle.pre_process
+		&& let Err(e) = hooks.pre_process(&mut value).await
+	{
+		failures.recover(e)?;
+		return Err(
+			failures
+				.finish(&info.origins, &info.unknown_keys)
+				.expect_err("a failure was recorded"),
+		);
+	}
+	if lifecycle.validate
+		&& let Err(e) = value.validate_config()
+	{
+		failures.recover(e)?;
+	}
+	failures.finish(&info.origins, &info.unknown_keys)?;
+	Ok(value)
+}
+
+/// Deserializes a merged document, pointing errors at the file that supplied
+/// the failing value and failing on unknown keys if `deny_unknown_keys` is set.
+///
+/// Strings are only coerced to the field type at `coercible` paths.
+///
+/// Returns `None` if a failure was recorded in accumulate mode.
+#[cfg(feature = "alloc")]
+pub(crate) fn deserialize_document<T: serde::de::DeserializeOwned>(
+	doc: Value,
+	origins: &Origins,
+	coercible: &Coercible,
+	deny_unknown_keys: bool,
+	failures: &mut Failures,
+) -> Result<Option<(T, Vec<UnknownKey>)>, FmtError> {
+	let (result, unknown) = doc.deserialize_collecting::<T>(coercible);
+	let result = result.map_err(|e| provenance::locate(e, origins, &failures.sources));
+	report_unknown(result, unknown, origins, deny_unknown_keys, failures)
+}
+
+/// Parses a single document straight into `T` with the format's own
+/// deserializer, so that no value is reinterpreted through a `Value`.
+///
+/// `doc`, the same document parsed as a `Value` if the format is
+/// self-describing, is only used to collect unknown keys.
+///
+/// Returns `None` if a failure was recorded in accumulate mode.
+#[cfg(feature = "alloc")]
+pub(crate) fn parse_direct<T: serde::de::DeserializeOwned, F: Format + ?Sized>(
+	format: &F,
+	key: &str,
+	bytes: &[u8],
+	doc: Option<Value>,
+	origins: &Origins,
+	deny_unknown_keys: bool,
+	failures: &mut Failures,
+) -> Result<Option<(T, Vec<UnknownKey>)>, FmtError> {
+	let unknown = match doc {
+		Some(doc) => doc.deserialize_collecting::<T>(&Coercible::new()).1,
+		None => Vec::new(),
+	};
+	let result = format.parse::<T>(bytes).map_err(|e| e.in_key(key));
+	report_unknown(result, unknown, origins, deny_unknown_keys, failures)
+}
+
+/// Records a failed deserialization, and the unknown keys, located through
+/// `origins`, if `deny_unknown_keys` is set or deserialization failed.
+#[cfg(feature = "alloc")]
+fn report_unknown<T>(
+	result: Result<T, FmtError>,
+	mut unknown: Vec<UnknownKey>,
+	origins: &Origins,
+	deny_unknown_keys: bool,
+	failures: &mut Failures,
+) -> Result<Option<(T, Vec<UnknownKey>)>, FmtError> {
+	provenance::locate_unknown(&mut unknown, origins, &failures.sources);
+	let value = match result {
+		Ok(value) => value,
+		Err(e) => {
+			failures.recover(e)?;
+			// A misspelled key often explains a missing field.
+			if !unknown.is_empty() {
+				let error = failures.unknown_keys(unknown);
+				failures.recover(error)?;
+			}
+			return Ok(None);
+		}
+	};
+	if deny_unknown_keys && !unknown.is_empty() {
+		let error = failures.unknown_keys(unknown);
+		failures.recover(error)?;
+		return Ok(Some((value, Vec::new())));
+	}
+	Ok(Some((value, unknown)))
+}
+
+#[cfg(feature = "alloc")]
+mod layered;
+#[cfg(feature = "alloc")]
+pub use layered::{Layer, LayeredLoader};
+
+#[cfg(feature = "std")]
+mod env;
+#[cfg(feature = "std")]
+pub use env::EnvOverlay;
+
+#[cfg(feature = "std")]
+mod interpolate;
+#[cfg(feature = "std")]
+pub use interpolate::Interpolator;
+
+#[cfg(feature = "jsonschema")]
+mod schema;
+#[cfg(feature = "jsonschema")]
+pub(crate) use schema::summary as schema_summary;
+#[cfg(feature = "jsonschema")]
+pub use schema::{DocumentSchema, SchemaViolation};
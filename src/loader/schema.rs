@@ -0,0 +1,208 @@
+/* src/loader/schema.rs */
+
+use crate::format::Json;
+use crate::value::Coercible;
+use crate::{FmtError, Format, Value};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::paths::LocationSegment;
+use jsonschema::{JsonType, Validator};
+
+/// A JSON Schema that parsed documents are checked against before they are
+/// deserialized.
+///
+/// The check runs on the merged document of any self-describing format, so
+/// a TOML or YAML file is validated as if it were JSON.
+pub struct DocumentSchema {
+	validator: Validator,
+}
+
+/// A single schema violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+	/// Field path of the offending value, e.g. `servers[0].port`; empty for
+	/// the document root.
+	pub path: String,
+	pub message: String,
+}
+
+impl core::fmt::Display for SchemaViolation {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		if self.path.is_empty() {
+			write!(f, "{}", self.message)
+		} else {
+			write!(f, "{}: {}", self.path, self.message)
+		}
+	}
+}
+
+impl DocumentSchema {
+	/// Compiles a schema given as a document tree.
+	pub fn new(schema: &Value) -> Result<Self, FmtError> {
+		let schema = serde_json::to_value(schema)
+			.map_err(|e| FmtError::parse(alloc::format!("invalid schema: {}", e)))?;
+		let validator = jsonschema::validator_for(&schema)
+			.map_err(|e| FmtError::parse(alloc::format!("invalid schema: {}", e)))?;
+		Ok(Self { validator })
+	}
+
+	/// Compiles a schema from JSON bytes, e.g. a `*.schema.json` file.
+	pub fn from_json(input: &[u8]) -> Result<Self, FmtError> {
+		Self::new(&Json.parse_value(input)?)
+	}
+
+	/// Compiles the schema generated for `T` (see `schema::schema_for`).
+	#[cfg(feature = "schema")]
+	pub fn of<T: crate::schema::JsonSchema>() -> Result<Self, FmtError> {
+		Self::from_json(&crate::schema::to_json::<T>()?)
+	}
+
+	/// Checks `document`, reporting every violation as `FmtError::Schema`.
+	pub fn check(&self, document: &Value) -> Result<(), FmtError> {
+		self.check_coerced(document, &Coercible::new())
+	}
+
+	/// Checks `document` after converting the strings at `coercible` paths
+	/// to the type the schema expects, as deserialization would, so that an
+	/// environment variable or override `"8080"` passes `"type": "integer"`.
+	pub(crate) fn check_coerced(
+		&self,
+		document: &Value,
+		coercible: &Coercible,
+	) -> Result<(), FmtError> {
+		let mut instance = serde_json::to_value(document)
+			.map_err(|e| FmtError::SerializeError(alloc::format!("{}", e)))?;
+		// A second pass coerces the items of strings split into arrays
+		for _ in 0..2 {
+			if coercible.is_empty() {
+				break;
+			}
+			let coerced: Vec<(String, serde_json::Value)> = self
+				.validator
+				.iter_errors(&instance)
+				.filter_map(|error| {
+					let ValidationErrorKind::Type { kind } = error.kind() else {
+						return None;
+					};
+					let serde_json::Value::String(text) = error.instance().as_ref() else {
+						return None;
+					};
+					let path = field_path(document, error.instance_path());
+					if !is_coercible(coercible, &path) {
+						return None;
+					}
+					Some((
+						String::from(error.instance_path().as_str()),
+						coerce(text, kind)?,
+					))
+				})
+				.collect();
+			if coerced.is_empty() {
+				break;
+			}
+			for (pointer, value) in coerced {
+				if let Some(slot) = instance.pointer_mut(&pointer) {
+					*slot = value;
+				}
+			}
+		}
+		let violations: Vec<SchemaViolation> = self
+			.validator
+			.iter_errors(&instance)
+			.map(|error| SchemaViolation {
+				path: field_path(document, error.instance_path()),
+				message: error.to_string(),
+			})
+			.collect();
+		if violations.is_empty() {
+			Ok(())
+		} else {
+			Err(FmtError::Schema(violations))
+		}
+	}
+}
+
+impl core::fmt::Debug for DocumentSchema {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("DocumentSchema").finish_non_exhaustive()
+	}
+}
+
+/// Joins violations into the single line shown by `FmtError::Schema`.
+pub(crate) fn summary(violations: &[SchemaViolation]) -> String {
+	violations
+		.iter()
+		.map(ToString::to_string)
+		.collect::<Vec<_>>()
+		.join("; ")
+}
+
+/// Whether `path` is at or below one of the `coercible` paths.
+fn is_coercible(coercible: &Coercible, path: &str) -> bool {
+	coercible.iter().any(|prefix| {
+		path
+			.strip_prefix(prefix.as_str())
+			.is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+	})
+}
+
+/// Converts `text` to the first of the expected types it parses as. Arrays
+/// are comma-separated lists, as for deserialization.
+fn coerce(text: &str, kind: &TypeKind) -> Option<serde_json::Value> {
+	let types: Vec<JsonType> = match kind {
+		TypeKind::Single(ty) => alloc::vec![*ty],
+		TypeKind::Multiple(types) => types.iter().collect(),
+	};
+	let trimmed = text.trim();
+	types.into_iter().find_map(|ty| match ty {
+		JsonType::Boolean => trimmed.parse::<bool>().ok().map(serde_json::Value::from),
+		JsonType::Integer => trimmed
+			.parse::<i64>()
+			.map(serde_json::Value::from)
+			.or_else(|_| trimmed.parse::<u64>().map(serde_json::Value::from))
+			.ok(),
+		JsonType::Number => trimmed
+			.parse::<f64>()
+			.ok()
+			.and_then(serde_json::Number::from_f64)
+			.map(serde_json::Value::Number),
+		JsonType::Array => Some(
+			text
+				.split(',')
+				.map(str::trim)
+				.filter(|item| !item.is_empty())
+				.map(serde_json::Value::from)
+				.collect(),
+		),
+		_ => None,
+	})
+}
+
+/// Converts a JSON pointer into a field path, using `document` to tell table
+/// keys that look like numbers from array indices.
+fn field_path(document: &Value, location: &jsonschema::paths::Location) -> String {
+	let mut path = String::new();
+	let mut node = Some(document);
+	for segment in location {
+		let key = match segment {
+			LocationSegment::Property(key) => key.into_owned(),
+			LocationSegment::Index(index) => index.to_string(),
+		};
+		match node {
+			Some(Value::Array(items)) => {
+				let index = key.parse::<usize>().ok();
+				node = index.and_then(|index| items.get(index));
+				path.push_str(&alloc::format!("[{}]", key));
+			}
+			_ => {
+				node = node.and_then(|node| node.as_table()?.get(&key));
+				if !path.is_empty() {
+					path.push('.');
+				}
+				path.push_str(&key);
+			}
+		}
+	}
+	path
+}
@@ -1,17 +1,38 @@
 /* src/loader/static_loader.rs */
 
+#[cfg(feature = "alloc")]
+use crate::provenance::{self, Origin, OriginKind, Origins, Positions};
+#[cfg(feature = "alloc")]
+use crate::value::Coercible;
+#[cfg(feature = "alloc")]
+use crate::{
+	AsyncPreProcess, FmtError, LoadResult, TryPreProcess, UnknownKey, ValidateConfig, Value,
+};
 use crate::{Format, Source};
 #[cfg(feature = "alloc")]
-use crate::{LoadResult, PreProcess, ValidateConfig};
+use alloc::string::String;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 #[cfg(feature = "alloc")]
 use serde::de::DeserializeOwned;
 
+#[cfg(feature = "jsonschema")]
+use super::DocumentSchema;
+
 /// A zero-cost loader that combines a specific Source and Format at compile time.
 pub struct StaticLoader<S, F> {
 	pub source: S,
 	pub format: F,
+	#[cfg(feature = "alloc")]
+	overrides: Vec<(String, Value)>,
+	#[cfg(feature = "jsonschema")]
+	schema: Option<DocumentSchema>,
+	#[cfg(feature = "alloc")]
+	deny_unknown_keys: bool,
+	#[cfg(feature = "alloc")]
+	accumulate_errors: bool,
+	#[cfg(feature = "alloc")]
+	lifecycle: super::Lifecycle,
 }
 
 impl<S, F> StaticLoader<S, F>
@@ -21,44 +42,229 @@ where
 {
 	/// Creates a new StaticLoader.
 	pub const fn new(source: S, format: F) -> Self {
-		Self { source, format }
+		Self {
+			source,
+			format,
+			#[cfg(feature = "alloc")]
+			overrides: Vec::new(),
+			#[cfg(feature = "jsonschema")]
+			schema: None,
+			#[cfg(feature = "alloc")]
+			deny_unknown_keys: false,
+			#[cfg(feature = "alloc")]
+			accumulate_errors: false,
+			#[cfg(feature = "alloc")]
+			lifecycle: super::Lifecycle::new(),
+		}
+	}
+
+	/// Replaces the value at a dotted path (e.g. `servers[0].host`) after parsing.
+	///
+	/// String values are coerced to the type of the target field. Loading fails
+	/// with `FmtError::PathNotFound` if the path does not exist in the document.
+	#[cfg(feature = "alloc")]
+	pub fn with_override(mut self, path: impl Into<String>, value: impl Into<Value>) -> Self {
+		self.overrides.push((path.into(), value.into()));
+		self
 	}
 
-	/// Loads and parses the configuration.
+	/// Fails loading with `FmtError::UnknownKeys` when the document has keys
+	/// that match no field of the target type, instead of listing them in
+	/// `LoadInfo::unknown_keys`.
+	#[cfg(feature = "alloc")]
+	pub fn deny_unknown_keys(mut self) -> Self {
+		self.deny_unknown_keys = true;
+		self
+	}
+
+	/// Reports every problem of a load at once as `FmtError::Multiple`
+	/// instead of stopping at the first; see `DynLoaderBuilder::accumulate_errors`.
+	#[cfg(feature = "alloc")]
+	pub fn accumulate_errors(mut self) -> Self {
+		self.accumulate_errors = true;
+		self
+	}
+
+	/// Does not call `set_load_context` on loaded values.
+	#[cfg(feature = "alloc")]
+	pub fn skip_context(mut self) -> Self {
+		self.lifecycle.context = false;
+		self
+	}
+
+	/// Does not run the pre-processing hooks on loaded values.
+	#[cfg(feature = "alloc")]
+	pub fn skip_pre_process(mut self) -> Self {
+		self.lifecycle.pre_process = false;
+		self
+	}
+
+	/// Returns loaded values without validating them.
+	#[cfg(feature = "alloc")]
+	pub fn skip_validation(mut self) -> Self {
+		self.lifecycle.validate = false;
+		self
+	}
+
+	/// Checks the parsed document against a JSON Schema before deserialization.
+	///
+	/// Violations fail the load with `FmtError::Schema`.
+	#[cfg(feature = "jsonschema")]
+	pub fn with_schema(mut self, schema: DocumentSchema) -> Self {
+		self.schema = Some(schema);
+		self
+	}
+
+	/// Loads and parses the configuration, then runs `set_load_context`,
+	/// `pre_process` and validation on the value.
 	#[cfg(feature = "alloc")]
 	pub async fn load<T>(&self, key: &str) -> LoadResult<T>
 	where
-		T: DeserializeOwned + PreProcess + ValidateConfig,
+		T: DeserializeOwned + TryPreProcess + ValidateConfig,
+	{
+		self.load_hooked(key, &super::SyncHooks).await
+	}
+
+	/// Loads `key` like `load`, running `AsyncPreProcess` with this loader's
+	/// source instead of `TryPreProcess`.
+	#[cfg(feature = "alloc")]
+	pub async fn load_with_source<T>(&self, key: &str) -> LoadResult<T>
+	where
+		T: DeserializeOwned + AsyncPreProcess + ValidateConfig,
+	{
+		self
+			.load_hooked(key, &super::SourceHooks(&self.source))
+			.await
+	}
+
+	#[cfg(feature = "alloc")]
+	async fn load_hooked<T>(&self, key: &str, hooks: &impl super::Hooks<T>) -> LoadResult<T>
+	where
+		T: DeserializeOwned + ValidateConfig,
 	{
 		// Source::read returns Result<Vec<u8>, ...> (requires alloc)
 		let bytes: Vec<u8> = match self.source.read(key).await {
 			Ok(b) => b,
-			Err(crate::FmtError::NotFound) => return LoadResult::NotFound,
+			Err(FmtError::NotFound) => return LoadResult::NotFound,
 			Err(e) => return LoadResult::Invalid(e),
 		};
 
-		match self.format.parse::<T>(&bytes) {
-			Ok(mut obj) => {
-				obj.pre_process();
-
-				LoadResult::Ok {
-					value: obj,
-					info: crate::LoadInfo {
-						#[cfg(feature = "std")]
-						path: std::path::PathBuf::from(key),
-						#[cfg(not(feature = "std"))]
-						key: alloc::string::String::from(key),
-						format: self
-							.format
-							.extensions()
-							.first()
-							.copied()
-							.unwrap_or("unknown"),
-						conflicts: Vec::new(),
-					},
+		let mut failures = super::Failures::new(self.accumulate_errors);
+		let mut origins = Origins::new();
+		let mut unknown_keys = Vec::new();
+		let parsed = self
+			.parse::<T>(key, &bytes, &mut origins, &mut failures)
+			.map(|parsed| {
+				parsed.map(|(value, unknown)| {
+					unknown_keys = unknown;
+					value
+				})
+			});
+		let mut info = crate::LoadInfo::new(key, self.format_name(), Vec::new());
+		info.origins = origins;
+		info.unknown_keys = unknown_keys;
+		let context = crate::LoadContext::new(&info, &self.source);
+		match super::complete(parsed, failures, &info, &context, hooks, self.lifecycle).await {
+			Ok(obj) => LoadResult::Ok { value: obj, info },
+			Err(e) => LoadResult::Invalid(e),
+		}
+	}
+
+	/// Parses the bytes, recording origins and collecting unknown keys for
+	/// self-describing formats. Overrides and a schema need the document, so
+	/// only then is the value deserialized from it.
+	///
+	/// Returns `None` if a failure was recorded in accumulate mode.
+	#[cfg(feature = "alloc")]
+	fn parse<T: DeserializeOwned>(
+		&self,
+		key: &str,
+		bytes: &[u8],
+		origins: &mut Origins,
+		failures: &mut super::Failures,
+	) -> Result<Option<(T, Vec<UnknownKey>)>, FmtError> {
+		let template = Origin {
+			format: Some(self.format_name()),
+			..Origin::new(OriginKind::File, key)
+		};
+		if !self.transforms_document() {
+			let doc = if self.format.self_describing() {
+				self.format.parse_value(bytes).ok()
+			} else {
+				None
+			};
+			if let Some(doc) = &doc {
+				provenance::record_document(origins, doc, &template, &Positions::new());
+			}
+			return super::parse_direct::<T, _>(
+				&self.format,
+				key,
+				bytes,
+				doc,
+				origins,
+				self.deny_unknown_keys,
+				failures,
+			);
+		}
+		if !self.format.self_describing() {
+			if !self.overrides.is_empty() {
+				return Err(FmtError::parse(
+					"format is not self-describing and cannot be overridden",
+				));
+			}
+			return Err(FmtError::parse(
+				"format is not self-describing and cannot be checked against a schema",
+			));
+		}
+
+		let mut doc = match self.format.parse_value(bytes) {
+			Ok(doc) => doc,
+			Err(e) => {
+				failures.recover(e.in_key(key))?;
+				return Ok(None);
+			}
+		};
+		provenance::record_document(origins, &doc, &template, &Positions::new());
+		let mut coercible = Coercible::new();
+		for (path, value) in &self.overrides {
+			match doc.set_path(path, value.clone()) {
+				Ok(()) => {
+					provenance::set(origins, path, Origin::new(OriginKind::Override, path));
+					coercible.insert(path.clone());
 				}
+				Err(e) => failures.recover(e)?,
 			}
-			Err(e) => LoadResult::Invalid(e),
 		}
+		if !failures.is_empty() {
+			return Ok(None);
+		}
+		#[cfg(feature = "jsonschema")]
+		if let Some(schema) = &self.schema
+			&& let Err(e) = schema.check_coerced(&doc, &coercible)
+		{
+			failures.recover(e)?;
+			return Ok(None);
+		}
+		super::deserialize_document::<T>(doc, origins, &coercible, self.deny_unknown_keys, failures)
+	}
+
+	/// Whether overrides or a schema need the parsed document.
+	#[cfg(feature = "alloc")]
+	fn transforms_document(&self) -> bool {
+		#[cfg(feature = "jsonschema")]
+		if self.schema.is_some() {
+			return true;
+		}
+		!self.overrides.is_empty()
+	}
+
+	#[cfg(feature = "alloc")]
+	fn format_name(&self) -> &'static str {
+		self
+			.format
+			.extensions()
+			.first()
+			.copied()
+			.unwrap_or("unknown")
 	}
 }
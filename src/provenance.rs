@@ -0,0 +1,239 @@
+/* src/provenance.rs */
+
+use crate::{FmtError, UnknownKey, Value};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Bound, Range};
+
+/// How a value entered the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OriginKind {
+	/// Not present in any document; the target type's default applies.
+	#[default]
+	Default,
+	/// Read from a file or source key.
+	File,
+	/// Overridden by an environment variable.
+	Env,
+	/// Overridden programmatically (e.g. `with_override`).
+	Override,
+}
+
+/// Where a single value came from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Origin {
+	pub kind: OriginKind,
+	/// Source key or file for `File`, variable name for `Env`, path for `Override`.
+	pub source: String,
+	/// Format name for values read from a file.
+	pub format: Option<&'static str>,
+	/// 1-based line, where the format reports positions.
+	pub line: Option<usize>,
+	/// 1-based column, where the format reports positions.
+	pub column: Option<usize>,
+	/// Byte range of the value in the file, where the format reports positions.
+	pub span: Option<Range<usize>>,
+}
+
+impl Origin {
+	pub(crate) fn new(kind: OriginKind, source: &str) -> Self {
+		Self {
+			kind,
+			source: String::from(source),
+			..Self::default()
+		}
+	}
+}
+
+/// Map from field path (e.g. `servers[0].host`) to origin.
+pub type Origins = BTreeMap<String, Origin>;
+
+/// Where a value sits in the document it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Position {
+	pub(crate) line: usize,
+	pub(crate) column: usize,
+	pub(crate) span: Range<usize>,
+	/// Byte range of the key naming the value.
+	pub(crate) key: Range<usize>,
+}
+
+/// Position of each field path in a document, tables included, for formats
+/// that report them.
+pub(crate) type Positions = BTreeMap<String, Position>;
+
+/// A parsed file, kept to show the lines that errors point at.
+#[derive(Debug)]
+pub(crate) struct SourceFile {
+	pub(crate) bytes: Vec<u8>,
+	pub(crate) positions: Positions,
+}
+
+/// Files whose values carry positions, by key.
+pub(crate) type Sources = BTreeMap<String, SourceFile>;
+
+/// Records `template` for every leaf of a freshly parsed `doc`.
+///
+/// Leaves are scalars, arrays and empty tables, matching how `Value::merge`
+/// replaces values.
+pub(crate) fn record_document(
+	origins: &mut Origins,
+	doc: &Value,
+	template: &Origin,
+	positions: &Positions,
+) {
+	let mut path = String::new();
+	record_leaves(origins, doc, &mut path, template, positions);
+}
+
+fn record_leaves(
+	origins: &mut Origins,
+	value: &Value,
+	path: &mut String,
+	template: &Origin,
+	positions: &Positions,
+) {
+	match value {
+		Value::Table(table) if !table.is_empty() => {
+			for (key, item) in table {
+				let len = path.len();
+				if !path.is_empty() {
+					path.push('.');
+				}
+				path.push_str(key);
+				record_leaves(origins, item, path, template, positions);
+				path.truncate(len);
+			}
+		}
+		_ => {
+			let mut origin = template.clone();
+			if let Some(position) = positions.get(path.as_str()) {
+				origin.line = Some(position.line);
+				origin.column = Some(position.column);
+				origin.span = Some(position.span.clone());
+			}
+			origins.insert(path.clone(), origin);
+		}
+	}
+}
+
+/// Records `origin` for a single replaced value, dropping the origins below it.
+///
+/// Ancestors are kept, so overriding one array element leaves the origin of
+/// the other elements intact.
+pub(crate) fn set(origins: &mut Origins, path: &str, origin: Origin) {
+	origins.retain(|existing, _| !is_descendant(existing, path));
+	origins.insert(String::from(path), origin);
+}
+
+/// Applies the origins of a merged overlay document on top of `origins`.
+///
+/// Each overlay leaf replaces the origins above and below it, since
+/// `Value::merge` replaces non-table values wholesale.
+pub(crate) fn overlay(origins: &mut Origins, other: Origins) {
+	for (path, origin) in other {
+		origins
+			.retain(|existing, _| !is_descendant(existing, &path) && !is_descendant(&path, existing));
+		origins.insert(path, origin);
+	}
+}
+
+/// Finds the origin of `path` or of its closest recorded ancestor.
+pub(crate) fn lookup<'a>(origins: &'a Origins, path: &str) -> Option<&'a Origin> {
+	let mut current = path;
+	loop {
+		if let Some(origin) = origins.get(current) {
+			return Some(origin);
+		}
+		let idx = current.rfind(['.', '['])?;
+		current = &current[..idx];
+	}
+}
+
+/// Points a deserialization error of a merged document at the file that
+/// supplied the failing value, when it came from one.
+///
+/// If the value's span and the file's bytes are known, the error shows the
+/// offending line like a parse error of the file itself.
+pub(crate) fn locate(error: FmtError, origins: &Origins, sources: &Sources) -> FmtError {
+	let FmtError::ParseError(mut error) = error else {
+		return error;
+	};
+	let origin = error
+		.path
+		.as_deref()
+		.and_then(|path| lookup(origins, path))
+		.filter(|origin| origin.kind == OriginKind::File);
+	if let Some(origin) = origin
+		&& error.key.is_none()
+	{
+		error.key = Some(origin.source.clone());
+		error.format = error.format.or(origin.format);
+		if error.line.is_none() {
+			match (&origin.span, sources.get(&origin.source)) {
+				(Some(span), Some(file)) => {
+					*error = core::mem::take(&mut *error).with_span(&file.bytes, span.clone());
+				}
+				_ => {
+					error.line = origin.line;
+					error.column = origin.column;
+				}
+			}
+		}
+	}
+	FmtError::ParseError(error)
+}
+
+/// Points unknown keys at the file and line they were read from.
+///
+/// A key is located by its own position if the file reports positions, and
+/// otherwise by the value recorded at, above or below it.
+pub(crate) fn locate_unknown(keys: &mut [UnknownKey], origins: &Origins, sources: &Sources) {
+	for key in keys {
+		let origin = lookup(origins, &key.path)
+			.or_else(|| {
+				origins
+					.range::<str, _>((Bound::Excluded(key.path.as_str()), Bound::Unbounded))
+					.find(|(path, _)| is_descendant(path, &key.path))
+					.map(|(_, origin)| origin)
+			})
+			.filter(|origin| origin.kind == OriginKind::File);
+		let Some(origin) = origin else {
+			continue;
+		};
+		key.file = Some(origin.source.clone());
+		let file = sources.get(&origin.source);
+		match file.and_then(|file| Some((file, file.positions.get(&key.path)?))) {
+			Some((file, position)) => {
+				let text = String::from_utf8_lossy(&file.bytes);
+				let (line, column) = line_column(&text, position.key.start);
+				key.line = Some(line);
+				key.column = Some(column);
+				key.span = Some(position.key.clone());
+			}
+			None => {
+				key.line = origin.line;
+				key.column = origin.column;
+			}
+		}
+	}
+}
+
+/// Whether `path` lies strictly below `ancestor`.
+fn is_descendant(path: &str, ancestor: &str) -> bool {
+	if ancestor.is_empty() {
+		return !path.is_empty();
+	}
+	path.len() > ancestor.len()
+		&& path.starts_with(ancestor)
+		&& matches!(path.as_bytes()[ancestor.len()], b'.' | b'[')
+}
+
+/// Converts a byte offset into a 1-based line and column.
+pub(crate) fn line_column(input: &str, offset: usize) -> (usize, usize) {
+	let before = &input[..offset.min(input.len())];
+	let line = before.matches('\n').count() + 1;
+	let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+	(line, column)
+}
@@ -0,0 +1,253 @@
+/* src/reload/file.rs */
+
+use super::{ConfigHandle, ErrorCallback, Publisher, Request};
+use crate::{AsyncPreProcess, DynLoader, FmtError, LoadInfo, Source, ValidateConfig};
+use notify::event::{EventKind, MetadataKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Default quiet period before a burst of events triggers a reload.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Reloads a `DynLoader` configuration whenever one of its files changes.
+///
+/// The parent directories of the loaded files are watched rather than the
+/// files themselves, so editors that save by renaming a temporary file over
+/// the original are picked up, as are newly created profile overlays.
+pub struct FileWatcher {
+	loader: DynLoader,
+	base_name: String,
+	debounce: Duration,
+	on_error: Option<ErrorCallback>,
+	secrets: Vec<String>,
+}
+
+impl FileWatcher {
+	/// Creates a watcher for `base_name`, resolved by `loader` as in `DynLoader::load`.
+	pub fn new(loader: DynLoader, base_name: impl Into<String>) -> Self {
+		Self {
+			loader,
+			base_name: base_name.into(),
+			debounce: DEFAULT_DEBOUNCE,
+			on_error: None,
+			secrets: Vec::new(),
+		}
+	}
+
+	/// Sets how long the files must stay quiet before reloading (default 100ms).
+	pub fn debounce(mut self, debounce: Duration) -> Self {
+		self.debounce = debounce;
+		self
+	}
+
+	/// Registers a callback for every failed reload, e.g. to log it or forward
+	/// it to a channel.
+	///
+	/// The last good value keeps being served until a reload succeeds; see
+	/// `ConfigHandle::error`.
+	pub fn on_error(mut self, on_error: impl Fn(&FmtError) + Send + Sync + 'static) -> Self {
+		self.on_error = Some(Box::new(on_error));
+		self
+	}
+
+	/// Marks a field path as secret, redacting its values in the published
+	/// diffs (see `Diff::redact`).
+	pub fn secret(mut self, path: impl Into<String>) -> Self {
+		self.secrets.push(path.into());
+		self
+	}
+
+	/// Loads the configuration and reloads it in a background task on change.
+	///
+	/// Must be called within a tokio runtime. Fails if the initial load fails
+	/// or the loader's source does not read from the local file system. A
+	/// reload that fails keeps the previous value.
+	pub async fn start<T>(self) -> Result<ConfigHandle<T>, FmtError>
+	where
+		T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig + Send + Sync + 'static,
+	{
+		let (publisher, info) =
+			Publisher::<T>::start(self.loader, self.base_name, self.on_error, self.secrets).await?;
+
+		let (events_tx, events) = mpsc::unbounded_channel();
+		let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+			if let Ok(event) = res
+				&& is_change(&event.kind)
+			{
+				let _ = events_tx.send(event.paths);
+			}
+		})
+		.map_err(watch_error)?;
+
+		let (requests_tx, requests) = mpsc::unbounded_channel();
+		let handle = publisher.handle(requests_tx);
+		let mut task = Task {
+			publisher,
+			debounce: self.debounce,
+			watcher,
+			targets: Targets::default(),
+		};
+		task.retarget(&info)?;
+
+		tokio::spawn(task.run(events, requests));
+		Ok(handle)
+	}
+}
+
+impl core::fmt::Debug for FileWatcher {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("FileWatcher")
+			.field("loader", &self.loader)
+			.field("base_name", &self.base_name)
+			.field("debounce", &self.debounce)
+			.field("on_error", &self.on_error.is_some())
+			.field("secrets", &self.secrets)
+			.finish()
+	}
+}
+
+struct Task<T> {
+	publisher: Publisher<T>,
+	debounce: Duration,
+	watcher: RecommendedWatcher,
+	targets: Targets,
+}
+
+impl<T> Task<T>
+where
+	T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig + Send + Sync,
+{
+	async fn run(
+		mut self,
+		mut events: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+		mut requests: mpsc::UnboundedReceiver<Request>,
+	) {
+		loop {
+			tokio::select! {
+				_ = self.publisher.closed() => return,
+				request = requests.recv() => {
+					let Some(reply) = request else { return };
+					let _ = reply.send(self.reload(true).await);
+					continue;
+				}
+				paths = events.recv() => match paths {
+					Some(paths) if self.targets.matches(&paths) => {}
+					Some(_) => continue,
+					None => return,
+				},
+			}
+
+			let mut deadline = Instant::now() + self.debounce;
+			loop {
+				match tokio::time::timeout_at(deadline, events.recv()).await {
+					Ok(Some(paths)) if self.targets.matches(&paths) => {
+						deadline = Instant::now() + self.debounce;
+					}
+					Ok(Some(_)) => {}
+					Ok(None) => return,
+					Err(_) => break,
+				}
+			}
+
+			let _ = self.reload(false).await;
+		}
+	}
+
+	/// Reloads the configuration; `force` publishes it even if the value
+	/// is unchanged.
+	async fn reload(&mut self, force: bool) -> Result<(), Arc<FmtError>> {
+		let info = self.publisher.reload(force).await?;
+		// Keep the previous targets if a new directory cannot be watched.
+		let _ = self.retarget(&info);
+		Ok(())
+	}
+
+	/// Watches the directories of every file that contributed to `info`.
+	fn retarget(&mut self, info: &LoadInfo) -> Result<(), FmtError> {
+		let source = self.publisher.loader.source();
+		let mut targets = Targets::default();
+
+		let (dir, name) = local_file(source, &self.publisher.base_name)?;
+		targets.dirs.insert(dir.clone());
+		targets.base = Some((dir, format!("{}.", name)));
+
+		for key in info.layers.iter().chain(&info.conflicts) {
+			let (dir, name) = local_file(source, &key.to_string_lossy())?;
+			targets.files.insert(dir.join(name));
+			targets.dirs.insert(dir);
+		}
+
+		for dir in targets.dirs.difference(&self.targets.dirs) {
+			self
+				.watcher
+				.watch(dir, RecursiveMode::NonRecursive)
+				.map_err(watch_error)?;
+		}
+		for dir in self.targets.dirs.difference(&targets.dirs) {
+			let _ = self.watcher.unwatch(dir);
+		}
+
+		self.targets = targets;
+		Ok(())
+	}
+}
+
+/// The files whose changes trigger a reload.
+#[derive(Default)]
+struct Targets {
+	dirs: HashSet<PathBuf>,
+	files: HashSet<PathBuf>,
+	/// Directory and file name prefix (`config.`) of the base name, matching
+	/// profile overlays and other formats that do not exist yet.
+	base: Option<(PathBuf, String)>,
+}
+
+impl Targets {
+	fn matches(&self, paths: &[PathBuf]) -> bool {
+		paths.iter().any(|path| {
+			self.files.contains(path)
+				|| self.base.as_ref().is_some_and(|(dir, prefix)| {
+					path.parent() == Some(dir.as_path())
+						&& path
+							.file_name()
+							.and_then(|name| name.to_str())
+							.is_some_and(|name| name.starts_with(prefix.as_str()))
+				})
+		})
+	}
+}
+
+/// Splits the local file behind `key` into its canonical directory and file name.
+fn local_file(source: &dyn Source, key: &str) -> Result<(PathBuf, String), FmtError> {
+	let path = source.local_path(key).ok_or(FmtError::Custom(
+		"source does not read from the local file system",
+	))?;
+	let name = path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.ok_or_else(|| FmtError::InvalidPath(String::from(key)))?;
+	let dir = match path.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => dir,
+		_ => Path::new("."),
+	};
+	Ok((std::fs::canonicalize(dir)?, String::from(name)))
+}
+
+/// Whether an event may have changed a file's contents.
+fn is_change(kind: &EventKind) -> bool {
+	!matches!(
+		kind,
+		EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime))
+	)
+}
+
+fn watch_error(e: notify::Error) -> FmtError {
+	FmtError::Io(std::io::Error::other(e))
+}
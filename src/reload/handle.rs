@@ -0,0 +1,94 @@
+/* src/reload/handle.rs */
+
+use super::{Request, Snapshot};
+use crate::{Diff, FmtError};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// A cheaply clonable view of a configuration that is reloaded in the background.
+///
+/// The background task stops once every handle has been dropped.
+pub struct ConfigHandle<T> {
+	rx: watch::Receiver<Snapshot<T>>,
+	errors: watch::Receiver<Option<Arc<FmtError>>>,
+	requests: mpsc::UnboundedSender<Request>,
+}
+
+impl<T> ConfigHandle<T> {
+	pub(super) fn new(
+		rx: watch::Receiver<Snapshot<T>>,
+		errors: watch::Receiver<Option<Arc<FmtError>>>,
+		requests: mpsc::UnboundedSender<Request>,
+	) -> Self {
+		Self {
+			rx,
+			errors,
+			requests,
+		}
+	}
+
+	/// The current value: the result of the last successful load.
+	pub fn get(&self) -> Arc<T> {
+		self.rx.borrow().value.clone()
+	}
+
+	/// The changes between the serialized previous value and the current one.
+	///
+	/// Empty for the initially loaded value. Secret fields are redacted.
+	pub fn diff(&self) -> Arc<Diff> {
+		self.rx.borrow().diff.clone()
+	}
+
+	/// The error of the last reload, if it failed.
+	///
+	/// While set, `get` keeps returning the last good value. The next
+	/// successful reload clears it.
+	pub fn error(&self) -> Option<Arc<FmtError>> {
+		self.errors.borrow().clone()
+	}
+
+	/// Waits until a new value is published and returns its changes.
+	///
+	/// Returns `None` once the background task has stopped.
+	pub async fn changed(&mut self) -> Option<Arc<Diff>> {
+		self.rx.changed().await.ok()?;
+		Some(self.rx.borrow_and_update().diff.clone())
+	}
+
+	/// Reloads immediately, even if no change was detected, and waits for the
+	/// result.
+	///
+	/// The new value is published even if it serializes like the previous one,
+	/// with an empty diff, so that resources read by `AsyncPreProcess` are
+	/// picked up.
+	///
+	/// On failure the previous value is kept and the error is returned.
+	pub async fn reload_now(&self) -> Result<(), Arc<FmtError>> {
+		let (tx, rx) = oneshot::channel();
+		self.requests.send(tx).map_err(|_| stopped())?;
+		rx.await.map_err(|_| stopped())?
+	}
+}
+
+fn stopped() -> Arc<FmtError> {
+	Arc::new(FmtError::Custom("reloader has stopped"))
+}
+
+impl<T> Clone for ConfigHandle<T> {
+	fn clone(&self) -> Self {
+		Self {
+			rx: self.rx.clone(),
+			errors: self.errors.clone(),
+			requests: self.requests.clone(),
+		}
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for ConfigHandle<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("ConfigHandle")
+			.field("value", &self.rx.borrow().value)
+			.field("error", &*self.errors.borrow())
+			.finish()
+	}
+}
@@ -0,0 +1,148 @@
+/* src/reload/mod.rs */
+
+#[cfg(feature = "watch")]
+mod file;
+mod handle;
+mod poll;
+
+#[cfg(feature = "watch")]
+pub use file::FileWatcher;
+pub use handle::ConfigHandle;
+pub use poll::Poller;
+
+use crate::{
+	AsyncPreProcess, Diff, DynLoader, FmtError, LoadInfo, LoadResult, ValidateConfig, Value,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// A `reload_now` request, answered once the reload has finished.
+type Request = oneshot::Sender<Result<(), Arc<FmtError>>>;
+
+/// Called with the error of every failed reload.
+type ErrorCallback = Box<dyn Fn(&FmtError) + Send + Sync>;
+
+/// A published value and the changes that produced it.
+struct Snapshot<T> {
+	value: Arc<T>,
+	diff: Arc<Diff>,
+}
+
+impl<T> Clone for Snapshot<T> {
+	fn clone(&self) -> Self {
+		Self {
+			value: self.value.clone(),
+			diff: self.diff.clone(),
+		}
+	}
+}
+
+/// Runs the load pipeline and publishes every successful result to the handles.
+///
+/// A failed reload keeps the last good value and is published as the error
+/// state instead, until the next successful reload clears it. Diffs compare
+/// the serialized values, so they do not depend on how the files spell them.
+/// A background reload whose value serializes like the previous one is not
+/// published; a `reload_now` is, with an empty diff.
+struct Publisher<T> {
+	loader: DynLoader,
+	base_name: String,
+	on_error: Option<ErrorCallback>,
+	/// Field paths redacted in published diffs.
+	secrets: Vec<String>,
+	/// The current value, serialized for diffs.
+	current: Value,
+	tx: watch::Sender<Snapshot<T>>,
+	errors: watch::Sender<Option<Arc<FmtError>>>,
+}
+
+impl<T> Publisher<T>
+where
+	T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig + Send + Sync,
+{
+	/// Performs the initial load.
+	async fn start(
+		loader: DynLoader,
+		base_name: String,
+		on_error: Option<ErrorCallback>,
+		secrets: Vec<String>,
+	) -> Result<(Self, LoadInfo), FmtError> {
+		let (value, info, current) = load::<T>(&loader, &base_name).await?;
+		let (tx, _) = watch::channel(Snapshot {
+			value: Arc::new(value),
+			diff: Arc::new(Diff::default()),
+		});
+		let (errors, _) = watch::channel(None);
+		Ok((
+			Self {
+				loader,
+				base_name,
+				on_error,
+				secrets,
+				current,
+				tx,
+				errors,
+			},
+			info,
+		))
+	}
+
+	/// Creates a handle whose `reload_now` requests are sent to `requests`.
+	fn handle(&self, requests: mpsc::UnboundedSender<Request>) -> ConfigHandle<T> {
+		ConfigHandle::new(self.tx.subscribe(), self.errors.subscribe(), requests)
+	}
+
+	/// Reloads the configuration, keeping the previous value on failure.
+	///
+	/// An unchanged value is only published if `force` is set, since it may
+	/// still differ in fields that are not serialized.
+	async fn reload(&mut self, force: bool) -> Result<LoadInfo, Arc<FmtError>> {
+		match load::<T>(&self.loader, &self.base_name).await {
+			Ok((value, info, current)) => {
+				self.errors.send_if_modified(|error| error.take().is_some());
+				if current == self.current && !force {
+					return Ok(info);
+				}
+
+				let mut diff = Diff::between(&self.current, &current);
+				diff.redact(&self.secrets);
+				self.current = current;
+				self.tx.send_replace(Snapshot {
+					value: Arc::new(value),
+					diff: Arc::new(diff),
+				});
+				Ok(info)
+			}
+			Err(e) => {
+				if let Some(on_error) = &self.on_error {
+					on_error(&e);
+				}
+				let e = Arc::new(e);
+				self.errors.send_replace(Some(e.clone()));
+				Err(e)
+			}
+		}
+	}
+
+	/// Completes once every handle has been dropped.
+	async fn closed(&self) {
+		self.tx.closed().await
+	}
+}
+
+/// Loads the configuration, also serializing it for diffs.
+async fn load<T>(loader: &DynLoader, base_name: &str) -> Result<(T, LoadInfo, Value), FmtError>
+where
+	T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig,
+{
+	match loader.load_with_source::<T>(base_name).await {
+		LoadResult::Ok { value, info } => {
+			let current = crate::value::to_value(&value)?;
+			Ok((value, info, current))
+		}
+		LoadResult::NotFound => Err(FmtError::NotFound),
+		LoadResult::Invalid(e) => Err(e),
+	}
+}
@@ -0,0 +1,186 @@
+/* src/reload/poll.rs */
+
+use super::{ConfigHandle, ErrorCallback, Publisher, Request};
+use crate::{AsyncPreProcess, DynLoader, FmtError, LoadInfo, ValidateConfig};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Default time between two polls.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reloads a `DynLoader` configuration when the content of its keys changes.
+///
+/// Works with any `Source`: every poll reads the contributing keys, plus the
+/// keys a new base file or profile overlay could appear at, and re-runs the
+/// pipeline only when the hash of their contents differs from the last poll.
+pub struct Poller {
+	loader: DynLoader,
+	base_name: String,
+	interval: Duration,
+	jitter: Duration,
+	on_error: Option<ErrorCallback>,
+	secrets: Vec<String>,
+}
+
+impl Poller {
+	/// Creates a poller for `base_name`, resolved by `loader` as in `DynLoader::load`.
+	pub fn new(loader: DynLoader, base_name: impl Into<String>) -> Self {
+		Self {
+			loader,
+			base_name: base_name.into(),
+			interval: DEFAULT_INTERVAL,
+			jitter: Duration::ZERO,
+			on_error: None,
+			secrets: Vec::new(),
+		}
+	}
+
+	/// Sets the time between two polls (default 5s).
+	pub fn interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	/// Adds a random delay of up to `jitter` to every interval (default none),
+	/// spreading the polls of many instances over time.
+	pub fn jitter(mut self, jitter: Duration) -> Self {
+		self.jitter = jitter;
+		self
+	}
+
+	/// Registers a callback for every failed reload, e.g. to log it or forward
+	/// it to a channel.
+	///
+	/// The last good value keeps being served until a reload succeeds; see
+	/// `ConfigHandle::error`.
+	pub fn on_error(mut self, on_error: impl Fn(&FmtError) + Send + Sync + 'static) -> Self {
+		self.on_error = Some(Box::new(on_error));
+		self
+	}
+
+	/// Marks a field path as secret, redacting its values in the published
+	/// diffs (see `Diff::redact`).
+	pub fn secret(mut self, path: impl Into<String>) -> Self {
+		self.secrets.push(path.into());
+		self
+	}
+
+	/// Loads the configuration and polls it in a background task.
+	///
+	/// Must be called within a tokio runtime. Fails if the initial load fails.
+	pub async fn start<T>(self) -> Result<ConfigHandle<T>, FmtError>
+	where
+		T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig + Send + Sync + 'static,
+	{
+		let (publisher, info) =
+			Publisher::<T>::start(self.loader, self.base_name, self.on_error, self.secrets).await?;
+		let fingerprint = fingerprint(&publisher, &info).await;
+
+		let (requests_tx, requests) = mpsc::unbounded_channel();
+		let handle = publisher.handle(requests_tx);
+		let task = Task {
+			publisher,
+			interval: self.interval,
+			jitter: self.jitter,
+			info,
+			fingerprint,
+		};
+		tokio::spawn(task.run(requests));
+		Ok(handle)
+	}
+}
+
+impl core::fmt::Debug for Poller {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Poller")
+			.field("loader", &self.loader)
+			.field("base_name", &self.base_name)
+			.field("interval", &self.interval)
+			.field("jitter", &self.jitter)
+			.field("on_error", &self.on_error.is_some())
+			.field("secrets", &self.secrets)
+			.finish()
+	}
+}
+
+struct Task<T> {
+	publisher: Publisher<T>,
+	interval: Duration,
+	jitter: Duration,
+	/// Metadata of the last successful load.
+	info: LoadInfo,
+	/// Content hash at the last reload attempt.
+	fingerprint: u64,
+}
+
+impl<T> Task<T>
+where
+	T: DeserializeOwned + Serialize + AsyncPreProcess + ValidateConfig + Send + Sync,
+{
+	async fn run(mut self, mut requests: mpsc::UnboundedReceiver<Request>) {
+		loop {
+			tokio::select! {
+				_ = self.publisher.closed() => return,
+				request = requests.recv() => {
+					let Some(reply) = request else { return };
+					let fingerprint = fingerprint(&self.publisher, &self.info).await;
+					let _ = reply.send(self.reload(fingerprint, true).await);
+				}
+				_ = tokio::time::sleep(self.delay()) => {
+					let fingerprint = fingerprint(&self.publisher, &self.info).await;
+					if fingerprint != self.fingerprint {
+						let _ = self.reload(fingerprint, false).await;
+					}
+				}
+			}
+		}
+	}
+
+	/// Reloads the configuration for the content hashed as `fingerprint`;
+	/// `force` publishes it even if the value is unchanged.
+	///
+	/// A failed reload still records the fingerprint, so invalid content is
+	/// only parsed again once it changes.
+	async fn reload(&mut self, fingerprint: u64, force: bool) -> Result<(), Arc<FmtError>> {
+		self.fingerprint = fingerprint;
+		self.info = self.publisher.reload(force).await?;
+		Ok(())
+	}
+
+	fn delay(&self) -> Duration {
+		if self.jitter.is_zero() {
+			return self.interval;
+		}
+		let random = RandomState::new().build_hasher().finish();
+		self.interval + self.jitter.mul_f64(random as f64 / u64::MAX as f64)
+	}
+}
+
+/// Hashes the content of every key that contributes, or could contribute, to
+/// the configuration.
+async fn fingerprint<T>(publisher: &Publisher<T>, info: &LoadInfo) -> u64 {
+	let loader = &publisher.loader;
+	let keys: BTreeSet<String> = loader
+		.candidates(&publisher.base_name)
+		.into_iter()
+		.chain(
+			info
+				.layers
+				.iter()
+				.map(|key| key.to_string_lossy().into_owned()),
+		)
+		.collect();
+
+	let mut hasher = DefaultHasher::new();
+	for key in keys {
+		key.hash(&mut hasher);
+		loader.source().read(&key).await.ok().hash(&mut hasher);
+	}
+	hasher.finish()
+}
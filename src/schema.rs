@@ -0,0 +1,60 @@
+/* src/schema.rs */
+
+use crate::format::{Json, Style};
+use crate::value::{Value, to_value};
+use crate::{FmtError, Format};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::ser::Serialize;
+
+pub use schemars::{JsonSchema, Schema};
+
+/// Property through which a document references its schema.
+const SCHEMA_PROPERTY: &str = "$schema";
+
+/// Generates the JSON Schema of `T`.
+///
+/// `validator` attributes on fields (`length`, `range`, `regex`, `email`,
+/// ...) are mapped to the matching schema keywords. The root additionally
+/// accepts a `$schema` string, so documents can reference their schema.
+pub fn schema_for<T: JsonSchema>() -> Schema {
+	let mut schema = schemars::schema_for!(T);
+	if let Some(properties) = schema
+		.get_mut("properties")
+		.and_then(|properties| properties.as_object_mut())
+	{
+		properties
+			.entry(SCHEMA_PROPERTY)
+			.or_insert_with(|| schemars::json_schema!({ "type": "string" }).to_value());
+	}
+	schema
+}
+
+/// Renders the JSON Schema of `T` as pretty-printed JSON.
+pub fn to_json<T: JsonSchema>() -> Result<Vec<u8>, FmtError> {
+	Json.serialize(&schema_for::<T>(), Style::Pretty)
+}
+
+/// Returns the key of the schema stored next to a configuration, e.g.
+/// `config.schema.json` for `config.toml`.
+pub fn schema_key(config_key: &str) -> String {
+	let name_start = config_key.rfind('/').map_or(0, |i| i + 1);
+	let stem = match config_key[name_start..].find('.') {
+		Some(dot) => &config_key[..name_start + dot],
+		None => config_key,
+	};
+	alloc::format!("{}.schema.json", stem)
+}
+
+/// Converts `value` into a document whose `$schema` property references
+/// `schema`, for editors that pick up the schema of JSON and YAML files.
+///
+/// Fails if `value` does not serialize to a table.
+pub fn embed_schema<T: Serialize + ?Sized>(value: &T, schema: &str) -> Result<Value, FmtError> {
+	let mut document = to_value(value)?;
+	document
+		.as_table_mut()
+		.ok_or_else(|| FmtError::SerializeError(String::from("`$schema` requires a table")))?
+		.insert(String::from(SCHEMA_PROPERTY), Value::from(schema));
+	Ok(document)
+}
@@ -1,9 +1,10 @@
 /* src/source/file.rs */
 
-use crate::{FmtError, Source};
+use crate::{FmtError, Source, WritableSource};
 #[cfg(feature = "alloc")]
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
 use tokio::fs;
 
 /// A file system source backed by tokio::fs.
@@ -46,6 +47,114 @@ impl FileSource {
 			Err(e) => Err(FmtError::Io(e)),
 		}
 	}
+
+	/// Resolves the directory entry for `key`, which need not exist yet.
+	///
+	/// The parent directory must exist inside the root. The entry itself is
+	/// not followed, but if it is a symlink its target must be inside the root
+	/// as well.
+	async fn resolve_entry(&self, key: &str) -> Result<PathBuf, FmtError> {
+		// Only plain relative components, which also rejects absolute keys
+		for component in Path::new(key).components() {
+			if !matches!(component, Component::Normal(_) | Component::CurDir) {
+				return Err(FmtError::SandboxViolation);
+			}
+		}
+
+		let path = self.root.join(key);
+		let name = path.file_name().ok_or(FmtError::SandboxViolation)?;
+		let parent = path.parent().ok_or(FmtError::SandboxViolation)?;
+
+		let canonical_root = fs::canonicalize(&self.root).await?;
+		let canonical_parent = fs::canonicalize(parent).await?;
+		if !canonical_parent.starts_with(&canonical_root) {
+			return Err(FmtError::SandboxViolation);
+		}
+
+		let entry = canonical_parent.join(name);
+		match fs::canonicalize(&entry).await {
+			Ok(target) if !target.starts_with(&canonical_root) => Err(FmtError::SandboxViolation),
+			_ => Ok(entry),
+		}
+	}
+}
+
+/// Writes `data` to `path` through a temporary file in the same directory.
+///
+/// Holds an exclusive advisory lock on the directory while writing (see
+/// `lock`). The temporary file is fsynced and takes over the permissions of
+/// the existing file before it is renamed over it.
+fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+	let dir = path.parent().unwrap_or(Path::new("."));
+	let name = path
+		.file_name()
+		.map(|name| name.to_string_lossy().into_owned())
+		.unwrap_or_default();
+
+	let lock = lock(dir, &name)?;
+
+	let tmp_path = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+	// A temporary file left over by a crashed writer of this process id
+	let _ = std::fs::remove_file(&tmp_path);
+
+	let result = (|| {
+		let mut tmp = std::fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.open(&tmp_path)?;
+		tmp.write_all(data)?;
+		if let Ok(metadata) = std::fs::metadata(path) {
+			tmp.set_permissions(metadata.permissions())?;
+		}
+		tmp.sync_all()?;
+		std::fs::rename(&tmp_path, path)?;
+		sync_dir(dir)
+	})();
+	if result.is_err() {
+		let _ = std::fs::remove_file(&tmp_path);
+	}
+
+	drop(lock);
+	result
+}
+
+/// Removes `path` while holding the same lock as `write_atomic`.
+fn remove_locked(path: &Path) -> std::io::Result<()> {
+	let dir = path.parent().unwrap_or(Path::new("."));
+	let name = path
+		.file_name()
+		.map(|name| name.to_string_lossy().into_owned())
+		.unwrap_or_default();
+
+	let _lock = lock(dir, &name)?;
+	std::fs::remove_file(path)?;
+	sync_dir(dir)
+}
+
+/// Exclusively locks the writes to `name` in `dir`, blocking until it is free.
+///
+/// On Unix the directory itself is locked, so nothing is left behind. Other
+/// platforms cannot open a directory as a file and lock a `.<name>.lock` file
+/// next to the target instead, which stays in place; its leading dot keeps it
+/// from matching the base name a reload watcher looks for.
+fn lock(dir: &Path, _name: &str) -> std::io::Result<std::fs::File> {
+	#[cfg(unix)]
+	let lock = std::fs::File::open(dir)?;
+	#[cfg(not(unix))]
+	let lock = std::fs::OpenOptions::new()
+		.create(true)
+		.truncate(false)
+		.write(true)
+		.open(dir.join(format!(".{}.lock", _name)))?;
+	lock.lock()?;
+	Ok(lock)
+}
+
+/// Persists a rename or removal in `dir`.
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+	#[cfg(unix)]
+	std::fs::File::open(_dir)?.sync_all()?;
+	Ok(())
 }
 
 #[cfg_attr(feature = "alloc", async_trait)]
@@ -58,4 +167,40 @@ impl Source for FileSource {
 	async fn exists(&self, key: &str) -> bool {
 		self.resolve_secure(key).await.is_ok()
 	}
+
+	fn local_path(&self, key: &str) -> Option<PathBuf> {
+		Some(self.root.join(key))
+	}
+
+	fn local_root(&self) -> Option<PathBuf> {
+		Some(self.root.clone())
+	}
+}
+
+#[async_trait]
+impl WritableSource for FileSource {
+	async fn write(&self, key: &str, data: &[u8]) -> Result<(), FmtError> {
+		let entry = self.resolve_entry(key).await?;
+		// Write through a symlink rather than replacing it
+		let path = fs::canonicalize(&entry).await.unwrap_or(entry);
+		let data = data.to_vec();
+		tokio::task::spawn_blocking(move || write_atomic(&path, &data))
+			.await
+			.map_err(|e| FmtError::Io(std::io::Error::other(e)))??;
+		Ok(())
+	}
+
+	async fn remove(&self, key: &str) -> Result<(), FmtError> {
+		let path = self.resolve_entry(key).await?;
+		if fs::symlink_metadata(&path).await.is_err() {
+			return Err(FmtError::NotFound);
+		}
+		tokio::task::spawn_blocking(move || remove_locked(&path))
+			.await
+			.map_err(|e| FmtError::Io(std::io::Error::other(e)))?
+			.map_err(|e| match e.kind() {
+				std::io::ErrorKind::NotFound => FmtError::NotFound,
+				_ => FmtError::Io(e),
+			})
+	}
 }
@@ -1,5 +1,7 @@
 /* src/source/memory.rs */
 
+#[cfg(feature = "std")]
+use crate::WritableSource;
 use crate::{FmtError, Source};
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
@@ -7,11 +9,17 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 #[cfg(feature = "alloc")]
 use async_trait::async_trait;
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
 
 /// A simple in-memory source useful for testing and embedded environments.
 #[derive(Default)]
 pub struct MemorySource {
+	#[cfg(not(feature = "std"))]
 	data: BTreeMap<String, Vec<u8>>,
+	/// Locked so that `WritableSource` can modify it through a shared reference.
+	#[cfg(feature = "std")]
+	data: RwLock<BTreeMap<String, Vec<u8>>>,
 }
 
 impl MemorySource {
@@ -22,17 +30,60 @@ impl MemorySource {
 
 	/// Inserts data into the source.
 	pub fn insert(&mut self, key: &str, value: Vec<u8>) {
-		self.data.insert(key.to_string(), value);
+		self.data_mut().insert(key.to_string(), value);
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn with_data<R>(&self, f: impl FnOnce(&BTreeMap<String, Vec<u8>>) -> R) -> R {
+		f(&self.data)
+	}
+
+	#[cfg(feature = "std")]
+	fn with_data<R>(&self, f: impl FnOnce(&BTreeMap<String, Vec<u8>>) -> R) -> R {
+		f(&self.data.read().unwrap_or_else(PoisonError::into_inner))
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn data_mut(&mut self) -> &mut BTreeMap<String, Vec<u8>> {
+		&mut self.data
+	}
+
+	#[cfg(feature = "std")]
+	fn data_mut(&mut self) -> &mut BTreeMap<String, Vec<u8>> {
+		self.data.get_mut().unwrap_or_else(PoisonError::into_inner)
 	}
 }
 
 #[cfg_attr(feature = "alloc", async_trait)]
 impl Source for MemorySource {
 	async fn read(&self, key: &str) -> Result<Vec<u8>, FmtError> {
-		self.data.get(key).cloned().ok_or(FmtError::NotFound)
+		self.with_data(|data| data.get(key).cloned().ok_or(FmtError::NotFound))
 	}
 
 	async fn exists(&self, key: &str) -> bool {
-		self.data.contains_key(key)
+		self.with_data(|data| data.contains_key(key))
+	}
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl WritableSource for MemorySource {
+	async fn write(&self, key: &str, data: &[u8]) -> Result<(), FmtError> {
+		self
+			.data
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(key.to_string(), data.to_vec());
+		Ok(())
+	}
+
+	async fn remove(&self, key: &str) -> Result<(), FmtError> {
+		self
+			.data
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.remove(key)
+			.map(|_| ())
+			.ok_or(FmtError::NotFound)
 	}
 }
@@ -0,0 +1,683 @@
+/* src/value/de.rs */
+
+use super::{Coercible, DATETIME_FIELD, DATETIME_NAME, Map, UnknownKey, Value};
+use crate::FmtError;
+use alloc::collections::btree_map;
+use alloc::string::String;
+use alloc::vec::{self, Vec};
+use core::cell::RefCell;
+use core::{fmt, iter};
+use serde::de::{
+	self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+	VariantAccess, Visitor,
+};
+
+// --- Value as a deserialization target ---
+
+impl<'de> de::Deserialize<'de> for Value {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_any(ValueVisitor)
+	}
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+	type Value = Value;
+
+	fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("any configuration value")
+	}
+
+	fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+		Ok(Value::Bool(v))
+	}
+
+	fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+		Ok(Value::Integer(v))
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+		Ok(Value::from(v))
+	}
+
+	fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+		Ok(Value::Float(v))
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+		Ok(Value::String(String::from(v)))
+	}
+
+	fn visit_string<E>(self, v: String) -> Result<Value, E> {
+		Ok(Value::String(v))
+	}
+
+	fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+		Ok(Value::Array(
+			v.iter().map(|b| Value::Integer(i64::from(*b))).collect(),
+		))
+	}
+
+	fn visit_none<E>(self) -> Result<Value, E> {
+		Ok(Value::Null)
+	}
+
+	fn visit_unit<E>(self) -> Result<Value, E> {
+		Ok(Value::Null)
+	}
+
+	fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+		de::Deserialize::deserialize(deserializer)
+	}
+
+	fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+		de::Deserialize::deserialize(deserializer)
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+		let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+		while let Some(item) = seq.next_element()? {
+			items.push(item);
+		}
+		Ok(Value::Array(items))
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+		let mut table = Map::new();
+		while let Some(key) = map.next_key::<String>()? {
+			// `toml` passes a datetime as a map with a single private field
+			if table.is_empty() && key == DATETIME_FIELD {
+				return Ok(Value::Datetime(map.next_value()?));
+			}
+			table.insert(key, map.next_value()?);
+		}
+		Ok(Value::Table(table))
+	}
+}
+
+// --- Value as a deserializer ---
+
+/// Strings at coercible paths are coerced to the requested scalar type, so
+/// values that only exist as text (e.g. environment variables) follow the type
+/// of the target field.
+macro_rules! deserialize_coerced {
+	($($method:ident => $visit:ident($ty:ty),)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+				match self.value {
+					Value::String(s) if self.coerces() => match s.trim().parse::<$ty>() {
+						Ok(v) => visitor.$visit(v),
+						Err(_) => Err(de::Error::invalid_type(de::Unexpected::Str(&s), &visitor)),
+					},
+					value => Self { value, track: self.track }.deserialize_any(visitor),
+				}
+			}
+		)*
+	};
+}
+
+macro_rules! forward_untracked {
+	($($method:ident)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+				ValueDeserializer::untracked(self).$method(visitor)
+			}
+		)*
+	};
+}
+
+impl<'de> IntoDeserializer<'de, FmtError> for Value {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+impl<'de> Deserializer<'de> for Value {
+	type Error = FmtError;
+
+	forward_untracked! {
+		deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+		deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+		deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+		deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+		deserialize_option deserialize_unit deserialize_seq deserialize_map
+		deserialize_identifier deserialize_ignored_any
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_unit_struct(name, visitor)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_newtype_struct(name, visitor)
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_tuple(len, visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_tuple_struct(name, len, visitor)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_struct(name, fields, visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		ValueDeserializer::untracked(self).deserialize_enum(name, variants, visitor)
+	}
+}
+
+/// Deserializes a `Value`, optionally recording the table keys that the
+/// target type has no field for and coercing strings at coercible paths.
+pub(crate) struct ValueDeserializer<'a> {
+	value: Value,
+	track: Option<Track<'a>>,
+}
+
+impl<'a> ValueDeserializer<'a> {
+	pub(crate) fn new(
+		value: Value,
+		unknown: Option<&'a RefCell<Vec<UnknownKey>>>,
+		coercible: &'a Coercible,
+	) -> Self {
+		Self {
+			value,
+			track: unknown.map(|unknown| Track {
+				unknown,
+				coercible,
+				coerce: false,
+				path: String::new(),
+			}),
+		}
+	}
+
+	fn untracked(value: Value) -> Self {
+		Self { value, track: None }
+	}
+
+	fn coerces(&self) -> bool {
+		self.track.as_ref().is_some_and(|track| track.coerce)
+	}
+}
+
+/// The position of a value being deserialized, where to record its unknown
+/// keys, and whether its strings are coerced.
+struct Track<'a> {
+	unknown: &'a RefCell<Vec<UnknownKey>>,
+	coercible: &'a Coercible,
+	/// Whether this value or one of its ancestors is at a coercible path.
+	coerce: bool,
+	path: String,
+}
+
+impl Track<'_> {
+	fn key(&self, key: &str) -> Self {
+		let path = if self.path.is_empty() {
+			String::from(key)
+		} else {
+			alloc::format!("{}.{}", self.path, key)
+		};
+		self.child(path)
+	}
+
+	fn index(&self, index: usize) -> Self {
+		self.child(alloc::format!("{}[{}]", self.path, index))
+	}
+
+	fn child(&self, path: String) -> Self {
+		Track {
+			unknown: self.unknown,
+			coercible: self.coercible,
+			coerce: self.coerce || self.coercible.contains(&path),
+			path,
+		}
+	}
+
+	/// Records the keys of a table deserialized as a struct that match none of
+	/// its fields (including aliases).
+	fn check(&self, table: &Map, fields: &'static [&'static str]) {
+		let mut unknown = self.unknown.borrow_mut();
+		for key in table.keys() {
+			// A root `$schema` references the document's JSON Schema.
+			if fields.contains(&key.as_str()) || (self.path.is_empty() && key == "$schema") {
+				continue;
+			}
+			unknown.push(UnknownKey::new(self.key(key).path, key, fields));
+		}
+	}
+}
+
+fn child<'a>(track: &Option<Track<'a>>, key: &str) -> Option<Track<'a>> {
+	track.as_ref().map(|track| track.key(key))
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+	type Error = FmtError;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		match self.value {
+			Value::Null => visitor.visit_unit(),
+			Value::Bool(v) => visitor.visit_bool(v),
+			Value::Integer(v) => visitor.visit_i64(v),
+			Value::UInteger(v) => visitor.visit_u64(v),
+			Value::Float(v) => visitor.visit_f64(v),
+			Value::String(v) | Value::Datetime(v) => visitor.visit_string(v),
+			Value::Array(items) => visit_array(items, self.track, visitor),
+			Value::Table(table) => visit_table(table, self.track, visitor),
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		match self.value {
+			Value::Null => visitor.visit_none(),
+			value => visitor.visit_some(Self {
+				value,
+				track: self.track,
+			}),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		match self.value {
+			Value::Datetime(v) if name == DATETIME_NAME => visitor.visit_map(
+				de::value::MapDeserializer::<_, FmtError>::new(iter::once((DATETIME_FIELD, v))),
+			),
+			Value::Table(table) => {
+				if let Some(track) = &self.track {
+					track.check(&table, fields);
+				}
+				visit_table(table, self.track, visitor)
+			}
+			value => Self {
+				value,
+				track: self.track,
+			}
+			.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		match self.value {
+			Value::String(variant) => visitor.visit_enum(EnumDeserializer {
+				variant,
+				value: None,
+				track: None,
+			}),
+			Value::Table(table) if table.len() == 1 => {
+				let (variant, value) = table.into_iter().next().expect("length checked");
+				visitor.visit_enum(EnumDeserializer {
+					track: child(&self.track, &variant),
+					variant,
+					value: Some(value),
+				})
+			}
+			other => Err(de::Error::invalid_type(unexpected(&other), &"enum")),
+		}
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		match self.value {
+			// A coerced string is a comma-separated list
+			Value::String(s) if self.coerces() => {
+				let items = s
+					.split(',')
+					.map(str::trim)
+					.filter(|item| !item.is_empty())
+					.map(Value::from)
+					.collect();
+				visit_array(items, self.track, visitor)
+			}
+			value => Self {
+				value,
+				track: self.track,
+			}
+			.deserialize_any(visitor),
+		}
+	}
+
+	deserialize_coerced! {
+		deserialize_bool => visit_bool(bool),
+		deserialize_i8 => visit_i64(i64),
+		deserialize_i16 => visit_i64(i64),
+		deserialize_i32 => visit_i64(i64),
+		deserialize_i64 => visit_i64(i64),
+		deserialize_u8 => visit_u64(u64),
+		deserialize_u16 => visit_u64(u64),
+		deserialize_u32 => visit_u64(u64),
+		deserialize_u64 => visit_u64(u64),
+		deserialize_f32 => visit_f64(f64),
+		deserialize_f64 => visit_f64(f64),
+	}
+
+	serde::forward_to_deserialize_any! {
+		i128 u128 char str string bytes byte_buf unit unit_struct tuple
+		tuple_struct map identifier
+	}
+}
+
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+	match value {
+		Value::Null => de::Unexpected::Unit,
+		Value::Bool(v) => de::Unexpected::Bool(*v),
+		Value::Integer(v) => de::Unexpected::Signed(*v),
+		Value::UInteger(v) => de::Unexpected::Unsigned(*v),
+		Value::Float(v) => de::Unexpected::Float(*v),
+		Value::String(v) => de::Unexpected::Str(v),
+		Value::Datetime(_) => de::Unexpected::Other("datetime"),
+		Value::Array(_) => de::Unexpected::Seq,
+		Value::Table(_) => de::Unexpected::Map,
+	}
+}
+
+fn visit_array<'de, V: Visitor<'de>>(
+	items: Vec<Value>,
+	track: Option<Track<'_>>,
+	visitor: V,
+) -> Result<V::Value, FmtError> {
+	let len = items.len();
+	let mut seq = SeqDeserializer {
+		iter: items.into_iter().enumerate(),
+		track,
+	};
+	let out = visitor.visit_seq(&mut seq)?;
+	if seq.iter.len() == 0 {
+		Ok(out)
+	} else {
+		Err(de::Error::invalid_length(len, &"fewer elements in array"))
+	}
+}
+
+fn visit_table<'de, V: Visitor<'de>>(
+	table: Map,
+	track: Option<Track<'_>>,
+	visitor: V,
+) -> Result<V::Value, FmtError> {
+	let mut map = MapDeserializer {
+		iter: table.into_iter(),
+		value: None,
+		track,
+	};
+	visitor.visit_map(&mut map)
+}
+
+struct SeqDeserializer<'a> {
+	iter: iter::Enumerate<vec::IntoIter<Value>>,
+	track: Option<Track<'a>>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'_> {
+	type Error = FmtError;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, FmtError> {
+		match self.iter.next() {
+			Some((index, value)) => seed
+				.deserialize(ValueDeserializer {
+					value,
+					track: self.track.as_ref().map(|track| track.index(index)),
+				})
+				.map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len())
+	}
+}
+
+struct MapDeserializer<'a> {
+	iter: btree_map::IntoIter<String, Value>,
+	value: Option<ValueDeserializer<'a>>,
+	track: Option<Track<'a>>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'_> {
+	type Error = FmtError;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, FmtError> {
+		match self.iter.next() {
+			Some((key, value)) => {
+				self.value = Some(ValueDeserializer {
+					value,
+					track: child(&self.track, &key),
+				});
+				seed.deserialize(MapKey(key)).map(Some)
+			}
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, FmtError> {
+		match self.value.take() {
+			Some(value) => seed.deserialize(value),
+			None => Err(de::Error::custom("value is missing")),
+		}
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len())
+	}
+}
+
+/// Parses a table key for the scalar key type of a map, such as the `u32` of
+/// a `HashMap<u32, String>`.
+struct MapKey(String);
+
+macro_rules! deserialize_key {
+	($($method:ident => $visit:ident($ty:ty),)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+				match self.0.parse::<$ty>() {
+					Ok(v) => visitor.$visit(v),
+					Err(_) => Err(de::Error::invalid_type(de::Unexpected::Str(&self.0), &visitor)),
+				}
+			}
+		)*
+	};
+}
+
+impl<'de> Deserializer<'de> for MapKey {
+	type Error = FmtError;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		visitor.visit_string(self.0)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FmtError> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		visitor.visit_enum(EnumDeserializer {
+			variant: self.0,
+			value: None,
+			track: None,
+		})
+	}
+
+	deserialize_key! {
+		deserialize_bool => visit_bool(bool),
+		deserialize_i8 => visit_i8(i8),
+		deserialize_i16 => visit_i16(i16),
+		deserialize_i32 => visit_i32(i32),
+		deserialize_i64 => visit_i64(i64),
+		deserialize_i128 => visit_i128(i128),
+		deserialize_u8 => visit_u8(u8),
+		deserialize_u16 => visit_u16(u16),
+		deserialize_u32 => visit_u32(u32),
+		deserialize_u64 => visit_u64(u64),
+		deserialize_u128 => visit_u128(u128),
+		deserialize_f32 => visit_f32(f32),
+		deserialize_f64 => visit_f64(f64),
+		deserialize_char => visit_char(char),
+	}
+
+	serde::forward_to_deserialize_any! {
+		str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+		identifier ignored_any
+	}
+}
+
+struct EnumDeserializer<'a> {
+	variant: String,
+	value: Option<Value>,
+	track: Option<Track<'a>>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for EnumDeserializer<'a> {
+	type Error = FmtError;
+	type Variant = VariantDeserializer<'a>;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(
+		self,
+		seed: V,
+	) -> Result<(V::Value, VariantDeserializer<'a>), FmtError> {
+		let variant = seed.deserialize(Value::String(self.variant))?;
+		Ok((
+			variant,
+			VariantDeserializer {
+				value: self.value,
+				track: self.track,
+			},
+		))
+	}
+}
+
+struct VariantDeserializer<'a> {
+	value: Option<Value>,
+	track: Option<Track<'a>>,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'_> {
+	type Error = FmtError;
+
+	fn unit_variant(self) -> Result<(), FmtError> {
+		match self.value {
+			None | Some(Value::Null) => Ok(()),
+			Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"unit variant")),
+		}
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, FmtError> {
+		match self.value {
+			Some(value) => seed.deserialize(ValueDeserializer {
+				value,
+				track: self.track,
+			}),
+			None => Err(de::Error::invalid_type(
+				de::Unexpected::UnitVariant,
+				&"newtype variant",
+			)),
+		}
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, FmtError> {
+		match self.value {
+			Some(Value::Array(items)) => visit_array(items, self.track, visitor),
+			Some(other) => Err(de::Error::invalid_type(
+				unexpected(&other),
+				&"tuple variant",
+			)),
+			None => Err(de::Error::invalid_type(
+				de::Unexpected::UnitVariant,
+				&"tuple variant",
+			)),
+		}
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, FmtError> {
+		match self.value {
+			Some(Value::Table(table)) => {
+				if let Some(track) = &self.track {
+					track.check(&table, fields);
+				}
+				visit_table(table, self.track, visitor)
+			}
+			Some(other) => Err(de::Error::invalid_type(
+				unexpected(&other),
+				&"struct variant",
+			)),
+			None => Err(de::Error::invalid_type(
+				de::Unexpected::UnitVariant,
+				&"struct variant",
+			)),
+		}
+	}
+}
@@ -0,0 +1,205 @@
+/* src/value/diff.rs */
+
+use super::Value;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Placeholder for the values of secret fields.
+const REDACTED: &str = "[redacted]";
+
+/// A single difference between two documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+	/// The path exists only in the new document.
+	Added { path: String, value: Value },
+	/// The path exists only in the old document.
+	Removed { path: String, value: Value },
+	/// The path exists in both documents with different values.
+	Changed {
+		path: String,
+		old: Value,
+		new: Value,
+	},
+}
+
+impl Change {
+	/// The field path (e.g. `servers[0].host`) of the change.
+	pub fn path(&self) -> &str {
+		match self {
+			Self::Added { path, .. } | Self::Removed { path, .. } | Self::Changed { path, .. } => path,
+		}
+	}
+}
+
+/// The structural difference between two documents, as a list of changed
+/// field paths.
+///
+/// Tables and arrays are compared entry by entry, so a change is reported at
+/// the deepest path that differs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diff {
+	changes: Vec<Change>,
+}
+
+impl Diff {
+	/// Compares `old` with `new`.
+	pub fn between(old: &Value, new: &Value) -> Self {
+		let mut diff = Self::default();
+		let mut path = String::new();
+		diff.compare(old, new, &mut path);
+		diff.changes.sort_by(|a, b| a.path().cmp(b.path()));
+		diff
+	}
+
+	/// Every change, ordered by path.
+	pub fn changes(&self) -> &[Change] {
+		&self.changes
+	}
+
+	/// Whether the documents are equal.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+
+	/// Whether anything at or below `path` changed, e.g. `touches("db")` for
+	/// any change of `db.*`.
+	pub fn touches(&self, path: &str) -> bool {
+		self
+			.changes
+			.iter()
+			.any(|change| covers(path, change.path()) || covers(change.path(), path))
+	}
+
+	/// Replaces the values of secret fields with a placeholder.
+	///
+	/// `secrets` are field paths whose array indices are ignored, so
+	/// `servers.password` also covers `servers[1].password`.
+	pub fn redact<S: AsRef<str>>(&mut self, secrets: &[S]) {
+		if secrets.is_empty() {
+			return;
+		}
+		for change in &mut self.changes {
+			match change {
+				Change::Added { path, value } | Change::Removed { path, value } => {
+					redact_value(value, path, secrets);
+				}
+				Change::Changed { path, old, new } => {
+					redact_value(old, path, secrets);
+					redact_value(new, path, secrets);
+				}
+			}
+		}
+	}
+
+	fn compare(&mut self, old: &Value, new: &Value, path: &mut String) {
+		match (old, new) {
+			(Value::Table(old), Value::Table(new)) => {
+				for (key, old_item) in old {
+					let len = push_key(path, key);
+					match new.get(key) {
+						Some(new_item) => self.compare(old_item, new_item, path),
+						None => self.changes.push(Change::Removed {
+							path: path.clone(),
+							value: old_item.clone(),
+						}),
+					}
+					path.truncate(len);
+				}
+				for (key, new_item) in new {
+					if !old.contains_key(key) {
+						let len = push_key(path, key);
+						self.changes.push(Change::Added {
+							path: path.clone(),
+							value: new_item.clone(),
+						});
+						path.truncate(len);
+					}
+				}
+			}
+			(Value::Array(old), Value::Array(new)) => {
+				for i in 0..old.len().max(new.len()) {
+					let len = path.len();
+					path.push_str(&alloc::format!("[{}]", i));
+					match (old.get(i), new.get(i)) {
+						(Some(old_item), Some(new_item)) => self.compare(old_item, new_item, path),
+						(Some(old_item), None) => self.changes.push(Change::Removed {
+							path: path.clone(),
+							value: old_item.clone(),
+						}),
+						(None, Some(new_item)) => self.changes.push(Change::Added {
+							path: path.clone(),
+							value: new_item.clone(),
+						}),
+						(None, None) => {}
+					}
+					path.truncate(len);
+				}
+			}
+			(old, new) if old != new => self.changes.push(Change::Changed {
+				path: path.clone(),
+				old: old.clone(),
+				new: new.clone(),
+			}),
+			_ => {}
+		}
+	}
+}
+
+fn push_key(path: &mut String, key: &str) -> usize {
+	let len = path.len();
+	if !path.is_empty() {
+		path.push('.');
+	}
+	path.push_str(key);
+	len
+}
+
+/// Whether `path` is `ancestor` or lies below it.
+fn covers(ancestor: &str, path: &str) -> bool {
+	ancestor.is_empty()
+		|| path == ancestor
+		|| (path.starts_with(ancestor) && matches!(path.as_bytes()[ancestor.len()], b'.' | b'['))
+}
+
+/// Replaces `value` at `path`, or any of its entries, when it is secret.
+fn redact_value<S: AsRef<str>>(value: &mut Value, path: &str, secrets: &[S]) {
+	let unindexed = strip_indices(path);
+	if secrets
+		.iter()
+		.any(|secret| covers(secret.as_ref(), &unindexed))
+	{
+		*value = Value::String(String::from(REDACTED));
+		return;
+	}
+
+	match value {
+		Value::Table(table) => {
+			for (key, item) in table.iter_mut() {
+				let mut child = String::from(path);
+				push_key(&mut child, key);
+				redact_value(item, &child, secrets);
+			}
+		}
+		Value::Array(items) => {
+			for item in items.iter_mut() {
+				redact_value(item, path, secrets);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Removes the `[n]` segments of a path.
+fn strip_indices(path: &str) -> String {
+	let mut out = String::with_capacity(path.len());
+	let mut in_index = false;
+	for c in path.chars() {
+		match c {
+			'[' => in_index = true,
+			']' => in_index = false,
+			c if !in_index => out.push(c),
+			_ => {}
+		}
+	}
+	out
+}
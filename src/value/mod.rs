@@ -0,0 +1,203 @@
+/* src/value/mod.rs */
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+mod de;
+mod diff;
+mod path;
+mod ser;
+mod unknown;
+
+pub use diff::{Change, Diff};
+pub(crate) use path::deserialize_tracked;
+pub use path::{Segment, parse_path};
+pub use ser::to_value;
+pub use unknown::UnknownKey;
+pub(crate) use unknown::summary as unknown_summary;
+
+/// The struct name and field through which the `toml` crate passes datetimes.
+const DATETIME_NAME: &str = "$__toml_private_Datetime";
+const DATETIME_FIELD: &str = "$__toml_private_datetime";
+
+/// A table of string keys to values.
+pub type Map = BTreeMap<String, Value>;
+
+/// Field paths of values inserted as text, such as environment variables and
+/// overrides. Strings at these paths, or below them, are coerced to the type
+/// of the target field; strings read from a document are not.
+pub(crate) type Coercible = alloc::collections::BTreeSet<String>;
+
+/// A format-independent document tree.
+///
+/// Self-describing formats (JSON, TOML, YAML) can be parsed into a `Value`,
+/// which allows documents from different sources to be merged before the final
+/// deserialization into the target type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+	#[default]
+	Null,
+	Bool(bool),
+	Integer(i64),
+	/// An unsigned integer above `i64::MAX`; smaller ones are `Integer`.
+	UInteger(u64),
+	Float(f64),
+	String(String),
+	/// A TOML date, time or date-time in its RFC 3339 text form.
+	///
+	/// It deserializes into TOML datetime types as well as strings, and
+	/// serializes as a string.
+	Datetime(String),
+	Array(Vec<Value>),
+	Table(Map),
+}
+
+impl Value {
+	/// Returns a short name of the value kind, used in error messages.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Null => "null",
+			Self::Bool(_) => "bool",
+			Self::Integer(_) | Self::UInteger(_) => "integer",
+			Self::Float(_) => "float",
+			Self::String(_) => "string",
+			Self::Datetime(_) => "datetime",
+			Self::Array(_) => "array",
+			Self::Table(_) => "table",
+		}
+	}
+
+	/// Returns the inner table, if this value is a table.
+	pub fn as_table(&self) -> Option<&Map> {
+		match self {
+			Self::Table(t) => Some(t),
+			_ => None,
+		}
+	}
+
+	/// Returns the inner table mutably, if this value is a table.
+	pub fn as_table_mut(&mut self) -> Option<&mut Map> {
+		match self {
+			Self::Table(t) => Some(t),
+			_ => None,
+		}
+	}
+
+	/// Returns the inner string, if this value is a string.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::String(s) => Some(s),
+			_ => None,
+		}
+	}
+
+	/// Deep-merges `other` on top of `self`.
+	///
+	/// Tables are merged key by key; any other value in `other` replaces the
+	/// value in `self`, including arrays.
+	pub fn merge(&mut self, other: Value) {
+		match (self, other) {
+			(Self::Table(base), Self::Table(overlay)) => {
+				for (key, value) in overlay {
+					match base.get_mut(&key) {
+						Some(existing) => existing.merge(value),
+						None => {
+							base.insert(key, value);
+						}
+					}
+				}
+			}
+			(this, other) => *this = other,
+		}
+	}
+
+	/// Deserializes the tree into the target type.
+	///
+	/// A failure reports the path of the offending field in `ParseError::path`.
+	pub fn deserialize_into<T: serde::de::DeserializeOwned>(self) -> Result<T, crate::FmtError> {
+		deserialize_tracked(self).map_err(|(e, path)| e.at_path(path))
+	}
+
+	/// Deserializes the tree like `deserialize_into`, also returning the table
+	/// keys that match no field of the target type or of its nested structs.
+	///
+	/// Structs using `#[serde(flatten)]` accept any key and are not checked.
+	pub fn deserialize_reporting_unknown<T: serde::de::DeserializeOwned>(
+		self,
+	) -> Result<(T, Vec<UnknownKey>), crate::FmtError> {
+		let (value, unknown) = self.deserialize_collecting(&Coercible::new());
+		Ok((value?, unknown))
+	}
+
+	/// Like `deserialize_reporting_unknown`, also returning the keys found
+	/// before a failure and coercing the strings at `coercible` paths.
+	pub(crate) fn deserialize_collecting<T: serde::de::DeserializeOwned>(
+		self,
+		coercible: &Coercible,
+	) -> (Result<T, crate::FmtError>, Vec<UnknownKey>) {
+		let unknown = core::cell::RefCell::new(Vec::new());
+		let deserializer = de::ValueDeserializer::new(self, Some(&unknown), coercible);
+		let value = deserialize_tracked(deserializer).map_err(|(e, path)| e.at_path(path));
+		(value, unknown.into_inner())
+	}
+}
+
+impl From<bool> for Value {
+	fn from(v: bool) -> Self {
+		Self::Bool(v)
+	}
+}
+
+macro_rules! from_integer {
+	($($ty:ty),*) => {
+		$(
+			impl From<$ty> for Value {
+				fn from(v: $ty) -> Self {
+					Self::Integer(i64::from(v))
+				}
+			}
+		)*
+	};
+}
+
+from_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for Value {
+	fn from(v: u64) -> Self {
+		match i64::try_from(v) {
+			Ok(i) => Self::Integer(i),
+			Err(_) => Self::UInteger(v),
+		}
+	}
+}
+
+impl From<f64> for Value {
+	fn from(v: f64) -> Self {
+		Self::Float(v)
+	}
+}
+
+impl From<&str> for Value {
+	fn from(v: &str) -> Self {
+		Self::String(String::from(v))
+	}
+}
+
+impl From<String> for Value {
+	fn from(v: String) -> Self {
+		Self::String(v)
+	}
+}
+
+impl From<Vec<Value>> for Value {
+	fn from(v: Vec<Value>) -> Self {
+		Self::Array(v)
+	}
+}
+
+impl From<Map> for Value {
+	fn from(v: Map) -> Self {
+		Self::Table(v)
+	}
+}
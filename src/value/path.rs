@@ -0,0 +1,97 @@
+/* src/value/path.rs */
+
+use super::Value;
+use crate::FmtError;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A single step of a dotted path such as `servers[0].host`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+	Key(String),
+	Index(usize),
+}
+
+/// Parses a dotted path with optional array indices.
+///
+/// `servers[0].host` yields `[Key("servers"), Index(0), Key("host")]`.
+pub fn parse_path(path: &str) -> Result<Vec<Segment>, FmtError> {
+	let invalid = || FmtError::InvalidPath(String::from(path));
+	let mut segments = Vec::new();
+
+	for part in path.split('.') {
+		let (key, mut rest) = match part.find('[') {
+			Some(idx) => (&part[..idx], &part[idx..]),
+			None => (part, ""),
+		};
+		if !key.is_empty() {
+			segments.push(Segment::Key(String::from(key)));
+		} else if rest.is_empty() {
+			return Err(invalid());
+		}
+		while !rest.is_empty() {
+			let end = rest.find(']').ok_or_else(invalid)?;
+			let index = rest[1..end].parse::<usize>().map_err(|_| invalid())?;
+			segments.push(Segment::Index(index));
+			rest = &rest[end + 1..];
+			if !rest.is_empty() && !rest.starts_with('[') {
+				return Err(invalid());
+			}
+		}
+	}
+
+	if segments.is_empty() {
+		return Err(invalid());
+	}
+	Ok(segments)
+}
+
+impl Value {
+	/// Returns the value at a dotted path, if it exists.
+	pub fn get_path(&self, path: &str) -> Option<&Value> {
+		let segments = parse_path(path).ok()?;
+		let mut current = self;
+		for segment in &segments {
+			current = match (segment, current) {
+				(Segment::Key(k), Self::Table(t)) => t.get(k)?,
+				(Segment::Index(i), Self::Array(a)) => a.get(*i)?,
+				_ => return None,
+			};
+		}
+		Some(current)
+	}
+
+	/// Replaces the value at a dotted path.
+	///
+	/// Returns `FmtError::PathNotFound` if any segment of the path does not exist.
+	pub fn set_path(&mut self, path: &str, value: Value) -> Result<(), FmtError> {
+		let segments = parse_path(path)?;
+		let mut current = self;
+		for segment in &segments {
+			let next = match (segment, current) {
+				(Segment::Key(k), Self::Table(t)) => t.get_mut(k),
+				(Segment::Index(i), Self::Array(a)) => a.get_mut(*i),
+				_ => None,
+			};
+			current = next.ok_or_else(|| FmtError::PathNotFound(String::from(path)))?;
+		}
+		*current = value;
+		Ok(())
+	}
+}
+
+/// Deserializes `T`, returning the field path (e.g. `servers[2].tls.port`)
+/// of a failure together with the error.
+pub(crate) fn deserialize_tracked<'de, D, T>(
+	deserializer: D,
+) -> Result<T, (D::Error, Option<String>)>
+where
+	D: serde::Deserializer<'de>,
+	T: serde::Deserialize<'de>,
+{
+	serde_path_to_error::deserialize(deserializer).map_err(|e| {
+		let path = alloc::format!("{}", e.path());
+		let path = (path != ".").then_some(path);
+		(e.into_inner(), path)
+	})
+}
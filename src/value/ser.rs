@@ -0,0 +1,527 @@
+/* src/value/ser.rs */
+
+use super::{DATETIME_FIELD, DATETIME_NAME, Map, Value};
+use crate::FmtError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Converts any serializable value into a document tree.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, FmtError> {
+	value.serialize(ValueSerializer)
+}
+
+impl Serialize for Value {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Self::Null => serializer.serialize_unit(),
+			Self::Bool(v) => serializer.serialize_bool(*v),
+			Self::Integer(v) => serializer.serialize_i64(*v),
+			Self::UInteger(v) => serializer.serialize_u64(*v),
+			Self::Float(v) => serializer.serialize_f64(*v),
+			Self::String(v) | Self::Datetime(v) => serializer.serialize_str(v),
+			Self::Array(items) => {
+				let mut seq = serializer.serialize_seq(Some(items.len()))?;
+				for item in items {
+					seq.serialize_element(item)?;
+				}
+				seq.end()
+			}
+			Self::Table(table) => {
+				let mut map = serializer.serialize_map(Some(table.len()))?;
+				for (k, v) in table {
+					map.serialize_entry(k, v)?;
+				}
+				map.end()
+			}
+		}
+	}
+}
+
+fn single(key: &str, value: Value) -> Value {
+	let mut table = Map::new();
+	table.insert(String::from(key), value);
+	Value::Table(table)
+}
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+	type Ok = Value;
+	type Error = FmtError;
+	type SerializeSeq = SerializeArray;
+	type SerializeTuple = SerializeArray;
+	type SerializeTupleStruct = SerializeArray;
+	type SerializeTupleVariant = SerializeArray;
+	type SerializeMap = SerializeTable;
+	type SerializeStruct = SerializeTable;
+	type SerializeStructVariant = SerializeTable;
+
+	fn serialize_bool(self, v: bool) -> Result<Value, FmtError> {
+		Ok(Value::Bool(v))
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v))
+	}
+
+	fn serialize_i128(self, v: i128) -> Result<Value, FmtError> {
+		i64::try_from(v)
+			.map(Value::Integer)
+			.map_err(|_| ser::Error::custom("integer out of range"))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Value, FmtError> {
+		Ok(Value::Integer(v.into()))
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Value, FmtError> {
+		Ok(Value::from(v))
+	}
+
+	fn serialize_u128(self, v: u128) -> Result<Value, FmtError> {
+		u64::try_from(v)
+			.map(Value::from)
+			.map_err(|_| ser::Error::custom("integer out of range"))
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Value, FmtError> {
+		Ok(Value::Float(v.into()))
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Value, FmtError> {
+		Ok(Value::Float(v))
+	}
+
+	fn serialize_char(self, v: char) -> Result<Value, FmtError> {
+		Ok(Value::String(v.to_string()))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Value, FmtError> {
+		Ok(Value::from(v))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<Value, FmtError> {
+		Ok(Value::Array(
+			v.iter().map(|b| Value::Integer((*b).into())).collect(),
+		))
+	}
+
+	fn serialize_none(self) -> Result<Value, FmtError> {
+		Ok(Value::Null)
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, FmtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Value, FmtError> {
+		Ok(Value::Null)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, FmtError> {
+		Ok(Value::Null)
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<Value, FmtError> {
+		Ok(Value::from(variant))
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<Value, FmtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<Value, FmtError> {
+		Ok(single(variant, value.serialize(self)?))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, FmtError> {
+		Ok(SerializeArray {
+			variant: None,
+			items: Vec::with_capacity(len.unwrap_or(0)),
+		})
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeArray, FmtError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		len: usize,
+	) -> Result<SerializeArray, FmtError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<SerializeArray, FmtError> {
+		Ok(SerializeArray {
+			variant: Some(variant),
+			items: Vec::with_capacity(len),
+		})
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<SerializeTable, FmtError> {
+		Ok(SerializeTable {
+			variant: None,
+			datetime: false,
+			table: Map::new(),
+			key: None,
+		})
+	}
+
+	fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeTable, FmtError> {
+		let mut table = self.serialize_map(Some(len))?;
+		table.datetime = name == DATETIME_NAME;
+		Ok(table)
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		_len: usize,
+	) -> Result<SerializeTable, FmtError> {
+		Ok(SerializeTable {
+			variant: Some(variant),
+			datetime: false,
+			table: Map::new(),
+			key: None,
+		})
+	}
+}
+
+struct SerializeArray {
+	variant: Option<&'static str>,
+	items: Vec<Value>,
+}
+
+impl SerializeArray {
+	fn finish(self) -> Value {
+		let array = Value::Array(self.items);
+		match self.variant {
+			Some(variant) => single(variant, array),
+			None => array,
+		}
+	}
+}
+
+impl ser::SerializeSeq for SerializeArray {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FmtError> {
+		self.items.push(value.serialize(ValueSerializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+impl ser::SerializeTuple for SerializeArray {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FmtError> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FmtError> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FmtError> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+struct SerializeTable {
+	variant: Option<&'static str>,
+	/// Whether this is a `toml` datetime, kept as `Value::Datetime`.
+	datetime: bool,
+	table: Map,
+	key: Option<String>,
+}
+
+impl SerializeTable {
+	fn finish(mut self) -> Value {
+		if self.datetime
+			&& let Some(Value::String(v)) = self.table.remove(DATETIME_FIELD)
+		{
+			return Value::Datetime(v);
+		}
+		let table = Value::Table(self.table);
+		match self.variant {
+			Some(variant) => single(variant, table),
+			None => table,
+		}
+	}
+}
+
+impl ser::SerializeMap for SerializeTable {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), FmtError> {
+		self.key = Some(key.serialize(KeySerializer)?);
+		Ok(())
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FmtError> {
+		let key = self
+			.key
+			.take()
+			.ok_or_else(|| <FmtError as ser::Error>::custom("map value without key"))?;
+		self.table.insert(key, value.serialize(ValueSerializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+impl ser::SerializeStruct for SerializeTable {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(
+		&mut self,
+		key: &'static str,
+		value: &T,
+	) -> Result<(), FmtError> {
+		self
+			.table
+			.insert(String::from(key), value.serialize(ValueSerializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+impl ser::SerializeStructVariant for SerializeTable {
+	type Ok = Value;
+	type Error = FmtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(
+		&mut self,
+		key: &'static str,
+		value: &T,
+	) -> Result<(), FmtError> {
+		ser::SerializeStruct::serialize_field(self, key, value)
+	}
+
+	fn end(self) -> Result<Value, FmtError> {
+		Ok(self.finish())
+	}
+}
+
+/// Serializes map keys, which must be representable as strings.
+struct KeySerializer;
+
+macro_rules! serialize_key_display {
+	($($method:ident($ty:ty),)*) => {
+		$(
+			fn $method(self, v: $ty) -> Result<String, FmtError> {
+				Ok(v.to_string())
+			}
+		)*
+	};
+}
+
+impl Serializer for KeySerializer {
+	type Ok = String;
+	type Error = FmtError;
+	type SerializeSeq = Impossible<String, FmtError>;
+	type SerializeTuple = Impossible<String, FmtError>;
+	type SerializeTupleStruct = Impossible<String, FmtError>;
+	type SerializeTupleVariant = Impossible<String, FmtError>;
+	type SerializeMap = Impossible<String, FmtError>;
+	type SerializeStruct = Impossible<String, FmtError>;
+	type SerializeStructVariant = Impossible<String, FmtError>;
+
+	serialize_key_display! {
+		serialize_bool(bool),
+		serialize_i8(i8),
+		serialize_i16(i16),
+		serialize_i32(i32),
+		serialize_i64(i64),
+		serialize_i128(i128),
+		serialize_u8(u8),
+		serialize_u16(u16),
+		serialize_u32(u32),
+		serialize_u64(u64),
+		serialize_u128(u128),
+		serialize_f32(f32),
+		serialize_f64(f64),
+		serialize_char(char),
+		serialize_str(&str),
+	}
+
+	fn serialize_bytes(self, _v: &[u8]) -> Result<String, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_none(self) -> Result<String, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, FmtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<String, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<String, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<String, FmtError> {
+		Ok(String::from(variant))
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<String, FmtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_value: &T,
+	) -> Result<String, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleStruct, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleVariant, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStruct, FmtError> {
+		Err(key_must_be_string())
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStructVariant, FmtError> {
+		Err(key_must_be_string())
+	}
+}
+
+fn key_must_be_string() -> FmtError {
+	ser::Error::custom("map key must be a string")
+}
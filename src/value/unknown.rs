@@ -0,0 +1,79 @@
+/* src/value/unknown.rs */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+/// A table key that matches no field of the type it was deserialized into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+	/// Field path of the key, e.g. `server.prot`.
+	pub path: String,
+	/// The closest field name accepted at that level, if one looks like a typo
+	/// of the key.
+	pub suggestion: Option<&'static str>,
+	/// Key of the file the key was read from, if known.
+	pub file: Option<String>,
+	/// 1-based line, where the format reports positions.
+	pub line: Option<usize>,
+	/// 1-based column, where the format reports positions.
+	pub column: Option<usize>,
+	/// Byte range of the key in the file, where the format reports positions.
+	pub span: Option<Range<usize>>,
+	/// The whole file `span` refers to, kept for rendering diagnostics.
+	#[cfg(feature = "diagnostics")]
+	pub source: Option<String>,
+}
+
+impl UnknownKey {
+	pub(crate) fn new(path: String, key: &str, fields: &'static [&'static str]) -> Self {
+		Self {
+			path,
+			suggestion: suggest(key, fields),
+			file: None,
+			line: None,
+			column: None,
+			span: None,
+			#[cfg(feature = "diagnostics")]
+			source: None,
+		}
+	}
+}
+
+impl fmt::Display for UnknownKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.path)?;
+		if let Some(suggestion) = self.suggestion {
+			write!(f, " (did you mean `{}`?)", suggestion)?;
+		}
+		Ok(())
+	}
+}
+
+/// Joins unknown keys into the single line shown by `FmtError::UnknownKeys`.
+pub(crate) fn summary(keys: &[UnknownKey]) -> String {
+	keys
+		.iter()
+		.map(ToString::to_string)
+		.collect::<Vec<_>>()
+		.join("; ")
+}
+
+/// Picks the field closest to `key` by edit distance, allowing roughly one
+/// edit per three characters.
+#[cfg(feature = "std")]
+fn suggest(key: &str, fields: &'static [&'static str]) -> Option<&'static str> {
+	let limit = key.chars().count().max(3) / 3;
+	fields
+		.iter()
+		.map(|field| (strsim::osa_distance(key, field), *field))
+		.filter(|(distance, _)| *distance <= limit)
+		.min_by_key(|(distance, _)| *distance)
+		.map(|(_, field)| field)
+}
+
+#[cfg(not(feature = "std"))]
+fn suggest(_key: &str, _fields: &'static [&'static str]) -> Option<&'static str> {
+	None
+}
@@ -0,0 +1,180 @@
+/* tests/aggregate_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{
+	DynLoader, ErrorKind, FmtError, FmtErrors, Interpolator, Layer, LayeredLoader, LoadResult,
+	MemorySource, PreProcess,
+};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Server {
+	#[cfg_attr(feature = "validate", validate(length(min = 1)))]
+	host: String,
+	#[cfg_attr(feature = "validate", validate(range(min = 1024)))]
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	#[cfg_attr(feature = "validate", validate(length(min = 1)))]
+	name: String,
+	#[cfg_attr(feature = "validate", validate(nested))]
+	servers: Vec<Server>,
+}
+
+impl PreProcess for TestConfig {}
+
+fn loader(files: &[(&str, &str)]) -> DynLoader {
+	let mut source = MemorySource::new();
+	for (key, content) in files {
+		source.insert(key, content.as_bytes().to_vec());
+	}
+	DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.includes()
+		.profile("prod")
+		.interpolate(Interpolator::new().vars([("HOST", "example.com")]))
+		.accumulate_errors()
+		.build()
+		.unwrap()
+}
+
+fn errors<T: core::fmt::Debug>(result: LoadResult<T>) -> FmtErrors {
+	match result {
+		LoadResult::Invalid(FmtError::Multiple(errors)) => errors,
+		other => panic!("Expected Invalid(Multiple), got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_accumulate_parse_errors_across_files() {
+	let loader = loader(&[
+		("config.toml", "include = [\"base.json\"]\nname = \"app\"\n"),
+		("base.json", "{\n  \"servers\": [ }"),
+		("config.prod.toml", "name = \"prod\"\nname = \"again\"\n"),
+	]);
+
+	let errors = errors(loader.load::<TestConfig>("config").await);
+	let files: Vec<_> = errors
+		.iter()
+		.map(|item| (item.kind, item.file.as_deref(), item.line))
+		.collect();
+	assert_eq!(
+		files,
+		[
+			(ErrorKind::Parse, Some("base.json"), Some(2)),
+			(ErrorKind::Parse, Some("config.prod.toml"), Some(2)),
+		]
+	);
+	assert!(
+		errors
+			.to_string()
+			.starts_with("2 problems found:\n  base.json:2:"),
+		"{}",
+		errors
+	);
+}
+
+#[tokio::test]
+async fn test_accumulate_interpolation_errors() {
+	let loader = loader(&[(
+		"config.toml",
+		"name = \"${MISSING}\"\n\n[[servers]]\nhost = \"${HOST}\"\nport = 8080\n\n[[servers]]\nhost = \"${servers[9].host}\"\nport = 8081\n",
+	)]);
+
+	let errors = errors(loader.load::<TestConfig>("config").await);
+	let items: Vec<_> = errors
+		.iter()
+		.map(|item| (item.kind, item.path.as_deref(), item.file.as_deref()))
+		.collect();
+	assert_eq!(
+		items,
+		[
+			(ErrorKind::Interpolation, Some("name"), Some("config.toml")),
+			(
+				ErrorKind::Interpolation,
+				Some("servers[1].host"),
+				Some("config.toml")
+			),
+		]
+	);
+}
+
+#[tokio::test]
+async fn test_accumulate_type_error_with_unknown_keys() {
+	let loader = loader(&[(
+		"config.json",
+		r#"{ "name": "app", "servers": [{ "host": "a", "prot": 8080 }] }"#,
+	)]);
+
+	let errors = errors(loader.load::<TestConfig>("config").await);
+	assert_eq!(
+		errors.to_string(),
+		"2 problems found:
+  config.json: servers[0]: missing field `port`
+  config.json: servers[0].prot: unknown key (did you mean `port`?)"
+	);
+}
+
+#[cfg(feature = "validate")]
+#[tokio::test]
+async fn test_accumulate_validation_errors() {
+	let loader = loader(&[
+		(
+			"config.toml",
+			"name = \"\"\n\n[[servers]]\nhost = \"\"\nport = 80\n",
+		),
+		("config.prod.json", r#"{ "extra": true }"#),
+	]);
+
+	let errors = errors(loader.load::<TestConfig>("config").await);
+	let items: Vec<_> = errors
+		.iter()
+		.map(|item| (item.kind, item.path.as_deref().unwrap_or_default()))
+		.collect();
+	assert_eq!(
+		items,
+		[
+			(ErrorKind::Validation, "name"),
+			(ErrorKind::Validation, "servers[0].host"),
+			(ErrorKind::Validation, "servers[0].port"),
+			(ErrorKind::UnknownKey, "extra"),
+		]
+	);
+	let port = errors.iter().nth(2).unwrap();
+	assert_eq!(port.file.as_deref(), Some("config.toml"));
+	assert_eq!(port.message, "failed `range` (min = 1024, value = 80)");
+	// Unknown keys are reported even though the value deserialized
+	let extra = errors.iter().nth(3).unwrap();
+	assert_eq!(extra.file.as_deref(), Some("config.prod.json"));
+}
+
+#[tokio::test]
+async fn test_accumulate_layers() {
+	let layer = |key: &str, content: &str, format| {
+		let mut source = MemorySource::new();
+		source.insert(key, content.as_bytes().to_vec());
+		Layer::new(source, key, format)
+	};
+	let loader = LayeredLoader::builder()
+		.layer(layer("defaults.json", "{ \"name\": ", AnyFormat::Json))
+		.layer(layer("user.toml", "name = \"mine\"\n", AnyFormat::Toml))
+		.layer(layer("local.toml", "[servers\n", AnyFormat::Toml))
+		.accumulate_errors()
+		.build()
+		.unwrap();
+
+	let errors = errors(loader.load::<TestConfig>().await);
+	let files: Vec<_> = errors.iter().map(|item| item.file.as_deref()).collect();
+	assert_eq!(files, [Some("defaults.json"), Some("local.toml")]);
+}
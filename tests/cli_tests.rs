@@ -0,0 +1,185 @@
+/* tests/cli_tests.rs */
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn fmtstruct(args: &[&str], stdin: &str) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_fmtstruct"))
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	// A command that fails early may exit without reading its input.
+	let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+	child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_cli_convert() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let input = tmp_dir.path().join("in.yaml");
+	let output = tmp_dir.path().join("out.toml");
+	std::fs::write(&input, "name: app\nserver:\n  port: 8080\n").unwrap();
+
+	let result = fmtstruct(
+		&["convert", input.to_str().unwrap(), output.to_str().unwrap()],
+		"",
+	);
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&output).unwrap(),
+		"name = \"app\"\n\n[server]\nport = 8080\n"
+	);
+
+	let result = fmtstruct(
+		&[
+			"convert",
+			"-",
+			"-",
+			"--from",
+			"toml",
+			"--to",
+			"json",
+			"--compact",
+		],
+		"a = 1\n",
+	);
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(String::from_utf8(result.stdout).unwrap(), "{\"a\":1}\n");
+
+	let result = fmtstruct(&["convert", "-", "out.json"], "{}");
+	assert_eq!(result.status.code(), Some(2));
+	assert!(String::from_utf8(result.stderr).unwrap().contains("--from"));
+}
+
+#[test]
+fn test_cli_fmt_check() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let path = tmp_dir.path().join("config.json");
+	let file = path.to_str().unwrap();
+	std::fs::write(&path, r#"{"b": 1, "a": [1, 2]}"#).unwrap();
+
+	let result = fmtstruct(&["fmt", "--check", file], "");
+	assert_eq!(result.status.code(), Some(1));
+	assert_eq!(String::from_utf8(result.stdout).unwrap().trim(), file);
+
+	let result = fmtstruct(&["fmt", file], "");
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&path).unwrap(),
+		"{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": 1\n}\n"
+	);
+
+	let result = fmtstruct(&["fmt", "--check", file], "");
+	assert!(result.status.success(), "{:?}", result);
+
+	let result = fmtstruct(&["fmt", "-", "--format", "yaml"], "b: 1\na: 2\n");
+	assert_eq!(String::from_utf8(result.stdout).unwrap(), "a: 2\nb: 1\n");
+
+	std::fs::write(&path, "{ invalid").unwrap();
+	let result = fmtstruct(&["fmt", "--check", file], "");
+	assert_eq!(result.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_fmt_keeps_toml_comments() {
+	let input = "# service\nname = \"app\"\n\n[server] # listener\nport=8080\nstarted = 1979-05-27T07:32:00Z\n\n[a]\nb = [1,2]\n";
+	let result = fmtstruct(&["fmt", "-", "--format", "toml"], input);
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(
+		String::from_utf8(result.stdout).unwrap(),
+		"# service\nname = \"app\"\n\n[a]\nb = [1, 2]\n\n[server] # listener\nport = 8080\nstarted = 1979-05-27T07:32:00Z\n"
+	);
+
+	let result = fmtstruct(&["fmt", "-", "--format", "yaml"], "b: 1 # keep\na: 2\n");
+	assert_eq!(result.status.code(), Some(2));
+	assert!(
+		String::from_utf8(result.stderr)
+			.unwrap()
+			.contains("comments")
+	);
+}
+
+#[test]
+fn test_cli_fmt_yaml_hash_in_data() {
+	let input = "b: 'it''s # data'\na: \"x #y\"\nc: |\n  # literal\n";
+	let result = fmtstruct(&["fmt", "-", "--format", "yaml"], input);
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(
+		String::from_utf8(result.stdout).unwrap(),
+		"a: 'x #y'\nb: 'it''s # data'\nc: |\n  # literal\n"
+	);
+
+	let result = fmtstruct(&["fmt", "-", "--format", "yaml"], "a: \"x #y\" # note\n");
+	assert_eq!(result.status.code(), Some(2));
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn test_cli_rejects_untyped_formats() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let output = tmp_dir.path().join("out.bin");
+
+	let result = fmtstruct(
+		&["convert", "-", output.to_str().unwrap(), "--from", "json"],
+		r#"{"a": 1}"#,
+	);
+	assert_eq!(result.status.code(), Some(2));
+	assert!(
+		String::from_utf8(result.stderr)
+			.unwrap()
+			.contains("not self-describing")
+	);
+	assert!(!output.exists());
+
+	let result = fmtstruct(
+		&["convert", "-", "-", "--from", "postcard", "--to", "json"],
+		"",
+	);
+	assert_eq!(result.status.code(), Some(2));
+
+	let result = fmtstruct(&["fmt", "-", "--format", "postcard"], "");
+	assert_eq!(result.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_convert_lossless() {
+	let result = fmtstruct(
+		&[
+			"convert",
+			"-",
+			"-",
+			"--from",
+			"toml",
+			"--to",
+			"json",
+			"--compact",
+		],
+		"when = 1979-05-27T07:32:00Z\n",
+	);
+	assert!(result.status.success(), "{:?}", result);
+	assert_eq!(
+		String::from_utf8(result.stdout).unwrap(),
+		"{\"when\":\"1979-05-27T07:32:00Z\"}\n"
+	);
+
+	let result = fmtstruct(
+		&["convert", "-", "-", "--from", "json", "--to", "toml"],
+		"{\"id\": 18446744073709551615}",
+	);
+	assert_eq!(result.status.code(), Some(2));
+	assert!(result.stdout.is_empty());
+
+	let result = fmtstruct(
+		&["convert", "-", "-", "--from", "json", "--to", "yaml"],
+		"{\"id\": 18446744073709551615}",
+	);
+	assert_eq!(
+		String::from_utf8(result.stdout).unwrap(),
+		"id: 18446744073709551615\n"
+	);
+}
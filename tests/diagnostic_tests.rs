@@ -0,0 +1,197 @@
+/* tests/diagnostic_tests.rs */
+
+#![cfg(all(feature = "diagnostics", feature = "toml"))]
+
+#[cfg(feature = "validate")]
+use fmtstruct::ValidateConfig;
+use fmtstruct::format::{AnyFormat, Toml};
+use fmtstruct::{DynLoader, FmtDiagnostic, FmtError, Format, LoadResult, MemorySource, PreProcess};
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Server {
+	#[cfg_attr(feature = "validate", validate(length(min = 1)))]
+	host: String,
+	#[cfg_attr(feature = "validate", validate(range(min = 1024)))]
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	#[cfg_attr(feature = "validate", validate(nested))]
+	server: Server,
+}
+
+impl PreProcess for TestConfig {}
+
+async fn load(content: &str) -> FmtError {
+	load_with(content, None).await
+}
+
+/// Loads `content`, merged with a `prod` profile overlay if one is given.
+async fn load_with(content: &str, profile: Option<&str>) -> FmtError {
+	let mut source = MemorySource::new();
+	source.insert("config.toml", content.as_bytes().to_vec());
+	if let Some(profile) = profile {
+		source.insert("config.prod.toml", profile.as_bytes().to_vec());
+	}
+	let mut builder = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.deny_unknown_keys();
+	if profile.is_some() {
+		builder = builder.profile("prod");
+	}
+	let loader = builder.build().unwrap();
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(e) => e,
+		other => panic!("Expected Invalid, got {:?}", other),
+	}
+}
+
+fn render(diagnostic: &FmtDiagnostic) -> String {
+	let mut out = String::new();
+	GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+		.with_width(80)
+		.render_report(&mut out, diagnostic)
+		.unwrap();
+	out
+}
+
+fn code(diagnostic: &FmtDiagnostic) -> String {
+	diagnostic.code().unwrap().to_string()
+}
+
+fn help(diagnostic: &FmtDiagnostic) -> String {
+	diagnostic.help().unwrap().to_string()
+}
+
+#[test]
+fn test_parse_error_diagnostic() {
+	let input = "[server]\nhost = \"localhost\"\nport = \"http\"\n";
+	let diagnostic = FmtDiagnostic::from(Toml.parse::<TestConfig>(input.as_bytes()).unwrap_err());
+	assert_eq!(code(&diagnostic), "fmtstruct::parse");
+	assert_eq!(diagnostic.to_string(), "parse error at `server.port`");
+	assert_eq!(
+		help(&diagnostic),
+		"check `server.port` against the target type"
+	);
+
+	let labels: Vec<_> = diagnostic.labels().unwrap().collect();
+	assert_eq!(labels.len(), 1);
+	assert_eq!((labels[0].offset(), labels[0].len()), (35, 6));
+	assert_eq!(
+		labels[0].label(),
+		Some("invalid type: string \"http\", expected u16")
+	);
+
+	let report = render(&diagnostic);
+	assert!(report.contains("[<input>:3:8]"), "{}", report);
+	assert!(report.contains(" 3 │ port = \"http\""), "{}", report);
+	assert!(!report.contains(" --> "), "{}", report);
+}
+
+#[tokio::test]
+async fn test_syntax_error_diagnostic() {
+	let diagnostic = FmtDiagnostic::from(load("[server\nport = 80\n").await);
+	assert_eq!(code(&diagnostic), "fmtstruct::parse");
+	assert_eq!(diagnostic.to_string(), "parse error");
+	assert_eq!(help(&diagnostic), "the input is not valid toml");
+	assert!(diagnostic.source_code().is_some());
+	let report = render(&diagnostic);
+	assert!(report.contains("[config.toml:1:8]"), "{}", report);
+}
+
+#[tokio::test]
+async fn test_loaded_type_error_diagnostic() {
+	// A single file is deserialized by the format itself, which knows the span
+	let diagnostic =
+		FmtDiagnostic::from(load("[server]\nhost = \"localhost\"\nport = \"http\"\n").await);
+	let labels: Vec<_> = diagnostic.labels().unwrap().collect();
+	assert_eq!((labels[0].offset(), labels[0].len()), (35, 6));
+	let report = render(&diagnostic);
+	assert!(report.contains("[config.toml:3:8]"), "{}", report);
+}
+
+#[tokio::test]
+async fn test_merged_type_error_diagnostic() {
+	// A type error in a merged document is labeled in the file it came from
+	let diagnostic = FmtDiagnostic::from(
+		load_with(
+			"[server]\nhost = \"localhost\"\nport = 8080\n",
+			Some("[server]\nport = \"http\"\n"),
+		)
+		.await,
+	);
+	assert_eq!(diagnostic.to_string(), "parse error at `server.port`");
+	let labels: Vec<_> = diagnostic.labels().unwrap().collect();
+	assert_eq!((labels[0].offset(), labels[0].len()), (16, 6));
+	let report = render(&diagnostic);
+	assert!(report.contains("[config.prod.toml:2:8]"), "{}", report);
+	assert!(report.contains(" 2 │ port = \"http\""), "{}", report);
+}
+
+#[tokio::test]
+async fn test_unknown_keys_diagnostic() {
+	let diagnostic = FmtDiagnostic::from(
+		load("[server]\nhost = \"localhost\"\nport = 8080\nprot = 8081\nverbose = true\n").await,
+	);
+	assert_eq!(code(&diagnostic), "fmtstruct::unknown_keys");
+	assert_eq!(
+		help(&diagnostic),
+		"did you mean `port` instead of `server.prot`?"
+	);
+
+	let labels: Vec<_> = diagnostic.labels().unwrap().collect();
+	let labels: Vec<_> = labels
+		.iter()
+		.map(|label| (label.offset(), label.len(), label.label()))
+		.collect();
+	assert_eq!(
+		labels,
+		[
+			(40, 4, Some("did you mean `port`?")),
+			(52, 7, Some("unknown key"))
+		]
+	);
+	let report = render(&diagnostic);
+	assert!(report.contains("[config.toml:4:1]"), "{}", report);
+	assert!(matches!(
+		diagnostic.into_inner(),
+		FmtError::UnknownKeys(keys) if keys.len() == 2
+	));
+}
+
+#[cfg(feature = "validate")]
+#[tokio::test]
+async fn test_validation_diagnostic() {
+	let config = TestConfig {
+		server: Server {
+			host: String::new(),
+			port: 80,
+		},
+	};
+	let diagnostic = FmtDiagnostic::from(config.validate_config().unwrap_err());
+	assert_eq!(code(&diagnostic), "fmtstruct::validation");
+	assert_eq!(diagnostic.to_string(), "validation failed");
+	assert_eq!(
+		help(&diagnostic),
+		"server.host: failed `length` (min = 1, value = \"\")\n\
+		 server.port: failed `range` (min = 1024, value = 80)"
+	);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_sandbox_diagnostic() {
+	let diagnostic = FmtDiagnostic::from(FmtError::SandboxViolation);
+	assert_eq!(code(&diagnostic), "fmtstruct::sandbox");
+	assert!(help(&diagnostic).contains("inside the source root"));
+	assert!(diagnostic.labels().is_none());
+}
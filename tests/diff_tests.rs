@@ -0,0 +1,76 @@
+/* tests/diff_tests.rs */
+
+#![cfg(feature = "json")]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{Change, Diff, Format, Value};
+
+fn doc(json: &str) -> Value {
+	AnyFormat::Json.parse_value(json.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_diff_paths() {
+	let old = doc(r#"{ "db": { "host": "a", "port": 1 }, "tags": ["x", "y"], "debug": true }"#);
+	let new = doc(r#"{ "db": { "host": "b", "port": 1 }, "tags": ["x"], "name": "app" }"#);
+
+	let diff = Diff::between(&old, &new);
+	assert_eq!(
+		diff.changes(),
+		[
+			Change::Changed {
+				path: "db.host".into(),
+				old: Value::from("a"),
+				new: Value::from("b"),
+			},
+			Change::Removed {
+				path: "debug".into(),
+				value: Value::Bool(true),
+			},
+			Change::Added {
+				path: "name".into(),
+				value: Value::from("app"),
+			},
+			Change::Removed {
+				path: "tags[1]".into(),
+				value: Value::from("y"),
+			},
+		]
+	);
+
+	assert!(diff.touches("db"));
+	assert!(diff.touches("db.host"));
+	assert!(!diff.touches("db.port"));
+	assert!(diff.touches("tags"));
+	assert!(Diff::between(&old, &old).is_empty());
+}
+
+#[test]
+fn test_diff_redact() {
+	let old = doc(r#"{ "db": { "password": "old" }, "servers": [{ "token": "t1" }] }"#);
+	let new = doc(
+		r#"{ "db": { "password": "new" }, "servers": [{ "token": "t2" }, { "token": "t3", "port": 1 }] }"#,
+	);
+
+	let mut diff = Diff::between(&old, &new);
+	diff.redact(&["db.password", "servers.token"]);
+
+	let redacted = Value::from("[redacted]");
+	assert_eq!(
+		diff.changes()[0],
+		Change::Changed {
+			path: "db.password".into(),
+			old: redacted.clone(),
+			new: redacted.clone(),
+		}
+	);
+	assert_eq!(diff.changes()[1].path(), "servers[0].token");
+	match &diff.changes()[2] {
+		Change::Added { path, value } => {
+			assert_eq!(path, "servers[1]");
+			assert_eq!(value.get_path("token"), Some(&redacted));
+			assert_eq!(value.get_path("port"), Some(&Value::Integer(1)));
+		}
+		other => panic!("Expected Added, got {:?}", other),
+	}
+}
@@ -0,0 +1,161 @@
+/* tests/edit_tests.rs */
+
+#![cfg(feature = "edit")]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, FmtError, MemorySource, PreProcess, Source, TomlEditor, Value};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Server {
+	host: String,
+	#[cfg_attr(feature = "validate", validate(range(min = 1)))]
+	port: u16,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	#[cfg_attr(feature = "validate", validate(nested))]
+	server: Server,
+	#[serde(default)]
+	tags: Vec<String>,
+}
+
+impl PreProcess for TestConfig {}
+
+const DOCUMENT: &str = r#"# Application settings
+name = "app" # display name
+
+# Listener
+[server]
+host = "localhost"
+port = 8080 # default port
+"#;
+
+#[test]
+fn test_edit_preserves_comments() {
+	let mut editor = TomlEditor::new("config.toml", DOCUMENT.as_bytes()).unwrap();
+	editor.set("server.port", 9090).unwrap();
+	editor.set("name", "service").unwrap();
+	editor.remove("server.host").unwrap();
+	editor.set("server.host", "0.0.0.0").unwrap();
+
+	assert_eq!(
+		editor.to_string(),
+		r#"# Application settings
+name = "service" # display name
+
+# Listener
+[server]
+port = 9090 # default port
+host = "0.0.0.0"
+"#
+	);
+}
+
+#[test]
+fn test_edit_creates_paths() {
+	let mut editor = TomlEditor::new("config.toml", DOCUMENT.as_bytes()).unwrap();
+	editor
+		.set("tags", Value::Array(vec!["a".into(), "b".into()]))
+		.unwrap();
+	editor.set("tags[2]", "c").unwrap();
+	editor.set("log.level", "debug").unwrap();
+
+	let output = editor.to_string();
+	assert!(
+		output.contains("tags = [\"a\", \"b\", \"c\"]"),
+		"{}",
+		output
+	);
+	assert!(output.contains("[log]\nlevel = \"debug\""), "{}", output);
+	assert!(output.starts_with("# Application settings\n"));
+
+	match editor.set("tags[9]", "z") {
+		Err(FmtError::PathNotFound(path)) => assert_eq!(path, "tags[9]"),
+		other => panic!("Expected PathNotFound, got {:?}", other),
+	}
+	match editor.remove("server.missing") {
+		Err(FmtError::PathNotFound(_)) => {}
+		other => panic!("Expected PathNotFound, got {:?}", other),
+	}
+	match editor.set("name.inner", 1) {
+		Err(FmtError::PathNotFound(_)) => {}
+		other => panic!("Expected PathNotFound, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_commit_checks_before_writing() {
+	let mut source = MemorySource::new();
+	source.insert("config.toml", DOCUMENT.as_bytes().to_vec());
+	let source = Arc::new(source);
+	let loader = DynLoader::builder()
+		.writable_source(SharedSource(source.clone()))
+		.format(AnyFormat::Toml)
+		.build()
+		.unwrap();
+
+	let mut editor = loader.edit_toml("config.toml").await.unwrap();
+	editor.set("server.port", "not a number").unwrap();
+	assert!(loader.commit_toml::<TestConfig>(&editor).await.is_err());
+	assert_eq!(
+		source.read("config.toml").await.unwrap(),
+		DOCUMENT.as_bytes()
+	);
+
+	#[cfg(feature = "validate")]
+	{
+		editor.set("server.port", 0).unwrap();
+		match loader.commit_toml::<TestConfig>(&editor).await {
+			Err(FmtError::Validation(_)) => {}
+			other => panic!("Expected Validation error, got {:?}", other),
+		}
+		assert_eq!(
+			source.read("config.toml").await.unwrap(),
+			DOCUMENT.as_bytes()
+		);
+	}
+
+	editor.set("server.port", 9090).unwrap();
+	let value = loader.commit_toml::<TestConfig>(&editor).await.unwrap();
+	assert_eq!(value.server.port, 9090);
+	let written = String::from_utf8(source.read("config.toml").await.unwrap()).unwrap();
+	assert!(
+		written.contains("port = 9090 # default port"),
+		"{}",
+		written
+	);
+}
+
+/// Shares a `MemorySource` with the test so it can inspect writes.
+struct SharedSource(Arc<MemorySource>);
+
+#[async_trait::async_trait]
+impl Source for SharedSource {
+	async fn read(&self, key: &str) -> Result<Vec<u8>, FmtError> {
+		self.0.read(key).await
+	}
+
+	async fn exists(&self, key: &str) -> bool {
+		self.0.exists(key).await
+	}
+}
+
+#[async_trait::async_trait]
+impl fmtstruct::WritableSource for SharedSource {
+	async fn write(&self, key: &str, data: &[u8]) -> Result<(), FmtError> {
+		self.0.write(key, data).await
+	}
+
+	async fn remove(&self, key: &str) -> Result<(), FmtError> {
+		self.0.remove(key).await
+	}
+}
@@ -0,0 +1,159 @@
+/* tests/env_tests.rs */
+
+#![cfg(all(feature = "json", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, EnvOverlay, FmtError, LoadResult, MemorySource, PreProcess};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	port: u16,
+	#[serde(default)]
+	tls: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	server: Server,
+	#[serde(default)]
+	origins: Vec<String>,
+	#[serde(default)]
+	ratio: Option<f64>,
+}
+
+impl PreProcess for TestConfig {}
+
+fn source() -> MemorySource {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.json",
+		r#"{ "server": { "host": "localhost", "port": 80 } }"#
+			.as_bytes()
+			.to_vec(),
+	);
+	source
+}
+
+#[tokio::test]
+async fn test_env_overrides_with_coercion() {
+	let env = EnvOverlay::new("APP").vars([
+		("APP__SERVER__PORT", "8080"),
+		("APP__SERVER__TLS", "true"),
+		("APP__ORIGINS", "a.com, b.com"),
+		("APP__RATIO", "0.5"),
+		("OTHER__SERVER__PORT", "1"),
+	]);
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.env(env)
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.server.host, "localhost");
+			assert_eq!(value.server.port, 8080);
+			assert!(value.server.tls);
+			assert_eq!(value.origins, vec!["a.com", "b.com"]);
+			assert_eq!(value.ratio, Some(0.5));
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_env_custom_separator_and_case() {
+	let env = EnvOverlay::new("APP")
+		.separator("_")
+		.case_sensitive()
+		.vars([("APP_server_host", "example.com"), ("APP_SERVER_PORT", "1")]);
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.env(env)
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.server.host, "example.com");
+			// Upper-case key does not match `server.port` without case folding
+			assert_eq!(value.server.port, 80);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_env_invalid_coercion() {
+	let env = EnvOverlay::new("APP").vars([("APP__SERVER__PORT", "not-a-port")]);
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.env(env)
+		.build()
+		.unwrap();
+
+	let result = loader.load::<TestConfig>("config").await;
+	assert!(matches!(result, LoadResult::Invalid(_)), "{:?}", result);
+}
+
+#[tokio::test]
+async fn test_file_strings_not_coerced() {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.json",
+		r#"{ "server": { "host": "localhost", "port": "80" }, "origins": "a.com" }"#
+			.as_bytes()
+			.to_vec(),
+	);
+	let env = EnvOverlay::new("APP").vars([("APP__SERVER__PORT", "8080")]);
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Json)
+		.env(env)
+		.build()
+		.unwrap();
+
+	// Only the variable is coerced; the file's string list stays a string
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::ParseError(e)) => {
+			assert_eq!(e.path.as_deref(), Some("origins"));
+		}
+		other => panic!("Expected ParseError, got {:?}", other),
+	}
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_env_skips_non_utf8_variables() {
+	use std::ffi::OsStr;
+	use std::os::unix::ffi::OsStrExt;
+
+	// SAFETY: no other test reads or writes these variables.
+	unsafe {
+		std::env::set_var("FMTSTRUCT_UTF8__SERVER__PORT", "8080");
+		std::env::set_var("FMTSTRUCT_UTF8__SERVER__HOST", OsStr::from_bytes(b"\xff"));
+	}
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.env(EnvOverlay::new("FMTSTRUCT_UTF8"))
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.server.host, "localhost");
+			assert_eq!(value.server.port, 8080);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
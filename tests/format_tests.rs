@@ -87,22 +87,38 @@ fn test_yaml_format() {
 #[test]
 fn test_postcard_format() {
 	use serde::Serialize;
-	#[derive(Serialize, Deserialize)]
+	use std::collections::BTreeMap;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	enum Mode {
+		Off,
+		Fixed(u8),
+		Range { min: u8, max: u8 },
+	}
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
 	struct PostcardTest {
 		name: String,
 		value: i32,
+		#[serde(skip)]
+		cache: u8,
+		mode: Option<Mode>,
+		modes: Vec<Mode>,
+		labels: BTreeMap<String, (u8, bool)>,
 	}
 
 	let original = PostcardTest {
 		name: "postcard".to_string(),
 		value: 4,
+		cache: 0,
+		mode: Some(Mode::Range { min: 1, max: 2 }),
+		modes: vec![Mode::Off, Mode::Fixed(3)],
+		labels: BTreeMap::from([("a".to_string(), (1, true))]),
 	};
 	let data = postcard::to_allocvec(&original).unwrap();
 
 	let pc = Postcard;
 	let cfg: PostcardTest = pc.parse(&data).unwrap();
-	assert_eq!(cfg.name, "postcard");
-	assert_eq!(cfg.value, 4);
+	assert_eq!(cfg, original);
 }
 
 #[cfg(feature = "json")]
@@ -115,3 +131,127 @@ fn test_parse_error() {
 	#[cfg(not(feature = "alloc"))]
 	assert!(matches!(result, Err(FmtError::ParseError)));
 }
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_parse_error_location() {
+	let input = "name = \"toml\"\nvalue = \"x\"\n";
+	let error = match Toml.parse::<TestConfig>(input.as_bytes()) {
+		Err(FmtError::ParseError(e)) => e,
+		other => panic!("Expected ParseError, got {:?}", other),
+	};
+	assert_eq!(error.format, Some("toml"));
+	assert_eq!((error.line, error.column), (Some(2), Some(9)));
+	assert_eq!(error.span, Some(22..25));
+	assert_eq!(error.source_line.as_deref(), Some("value = \"x\""));
+	assert_eq!(
+		error.to_string(),
+		"parse error at `value`: invalid type: string \"x\", expected i32
+ --> <input>:2:9 (toml)
+  |
+2 | value = \"x\"
+  |         ^^^"
+	);
+}
+
+#[cfg(all(feature = "json", feature = "yaml"))]
+#[test]
+fn test_parse_error_line_column() {
+	let error = match Json.parse::<TestConfig>(b"{\n  \"name\": }") {
+		Err(FmtError::ParseError(e)) => e,
+		other => panic!("Expected ParseError, got {:?}", other),
+	};
+	assert_eq!(error.message, "expected value");
+	assert_eq!((error.line, error.column), (Some(2), Some(11)));
+	assert_eq!(error.source_line.as_deref(), Some("  \"name\": }"));
+
+	let error = match Yaml.parse::<TestConfig>(b"name: [a\n") {
+		Err(FmtError::ParseError(e)) => e,
+		other => panic!("Expected ParseError, got {:?}", other),
+	};
+	assert_eq!(error.format, Some("yaml"));
+	assert_eq!((error.line, error.column), (Some(1), Some(7)));
+	assert!(error.to_string().ends_with("1 | name: [a\n  |       ^"));
+
+	// A zero column from hand-built errors points at the start of the line
+	let error = fmtstruct::ParseError {
+		line: Some(1),
+		column: Some(0),
+		source_line: Some(String::from("name: [a")),
+		..fmtstruct::ParseError::new("expected value")
+	};
+	assert!(error.to_string().ends_with("1 | name: [a\n  | ^"));
+}
+
+#[cfg(all(
+	feature = "json",
+	feature = "toml",
+	feature = "yaml",
+	feature = "postcard"
+))]
+#[test]
+fn test_parse_error_path() {
+	#[derive(Debug, Deserialize)]
+	struct Tls {
+		port: u16,
+	}
+	#[derive(Debug, Deserialize)]
+	struct Server {
+		host: String,
+		tls: Tls,
+	}
+	#[derive(Debug, Deserialize)]
+	struct Servers {
+		servers: Vec<Server>,
+	}
+
+	fn error<F: Format>(format: F, input: &[u8]) -> fmtstruct::ParseError {
+		match format.parse::<Servers>(input) {
+			Err(FmtError::ParseError(e)) => *e,
+			other => panic!("Expected ParseError, got {:?}", other),
+		}
+	}
+
+	let valid = br#"{"servers": [{"host": "a", "tls": {"port": 443}}]}"#;
+	let parsed: Servers = Json.parse(valid).unwrap();
+	assert_eq!(parsed.servers[0].host, "a");
+	assert_eq!(parsed.servers[0].tls.port, 443);
+
+	let json = br#"{"servers": [
+		{"host": "a", "tls": {"port": 443}},
+		{"host": "b", "tls": {"port": "x"}}
+	]}"#;
+	let e = error(Json, json);
+	assert_eq!(e.path.as_deref(), Some("servers[1].tls.port"));
+	assert_eq!(e.line, Some(3));
+
+	let toml =
+		b"[[servers]]\nhost = \"a\"\ntls.port = 443\n\n[[servers]]\nhost = \"b\"\ntls.port = 70000\n";
+	let e = error(Toml, toml);
+	assert_eq!(e.path.as_deref(), Some("servers[1].tls.port"));
+	assert_eq!(e.line, Some(7));
+
+	let yaml = b"servers:\n  - host: a\n    tls: {}\n";
+	let e = error(Yaml, yaml);
+	assert_eq!(e.path.as_deref(), Some("servers[0].tls"));
+	assert_eq!(e.message, "missing field `port`");
+	assert!(
+		e.to_string()
+			.starts_with("parse error at `servers[0].tls`: missing field `port`"),
+		"{}",
+		e
+	);
+
+	// Postcard has no field names on the wire; the path takes them from the
+	// target type. The second server is missing, failing at its `host`.
+	let bytes = postcard::to_allocvec(&("a", (443u16,))).unwrap();
+	let e = error(Postcard, &[&[2u8][..], &bytes].concat());
+	assert_eq!(e.path.as_deref(), Some("servers[1].host"));
+
+	let valid: Servers = Postcard.parse(&[&[1u8][..], &bytes].concat()).unwrap();
+	assert_eq!(valid.servers[0].tls.port, 443);
+
+	let bytes = postcard::to_allocvec(&(2u8, "a", 443u16, "b")).unwrap();
+	let e = error(Postcard, &bytes);
+	assert_eq!(e.path.as_deref(), Some("servers[1].tls.port"));
+}
@@ -0,0 +1,153 @@
+/* tests/include_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, FmtError, LoadResult, MemorySource, PreProcess};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+#[serde(deny_unknown_fields)]
+struct TestConfig {
+	name: String,
+	port: u16,
+	db: String,
+}
+
+impl PreProcess for TestConfig {}
+
+fn loader(source: MemorySource) -> DynLoader {
+	DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.includes()
+		.build()
+		.unwrap()
+}
+
+#[tokio::test]
+async fn test_include_relative_and_stripped() {
+	let mut source = MemorySource::new();
+	source.insert(
+		"app/config.toml",
+		b"include = [\"common.toml\", \"../shared/db.json\"]\nport = 8080\n".to_vec(),
+	);
+	source.insert("app/common.toml", b"name = \"app\"\nport = 80\n".to_vec());
+	source.insert("shared/db.json", br#"{ "db": "postgres" }"#.to_vec());
+
+	match loader(source).load::<TestConfig>("app/config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.name, "app");
+			assert_eq!(value.port, 8080);
+			assert_eq!(value.db, "postgres");
+			assert_eq!(
+				info.layers,
+				vec![
+					PathBuf::from("app/common.toml"),
+					PathBuf::from("shared/db.json"),
+					PathBuf::from("app/config.toml"),
+				]
+			);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_extends_nested() {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.toml",
+		b"extends = \"base.toml\"\nport = 1\n".to_vec(),
+	);
+	source.insert(
+		"base.toml",
+		b"extends = \"root.toml\"\nname = \"base\"\n".to_vec(),
+	);
+	source.insert("root.toml", b"name = \"root\"\ndb = \"sqlite\"\n".to_vec());
+
+	match loader(source).load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.name, "base");
+			assert_eq!(value.db, "sqlite");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_include_cycle() {
+	let mut source = MemorySource::new();
+	source.insert("config.toml", b"include = \"a.toml\"\n".to_vec());
+	source.insert("a.toml", b"include = \"b.toml\"\n".to_vec());
+	source.insert("b.toml", b"include = \"a.toml\"\n".to_vec());
+
+	match loader(source).load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::IncludeCycle(chain)) => {
+			assert_eq!(chain, "config.toml -> a.toml -> b.toml -> a.toml");
+		}
+		other => panic!("Expected IncludeCycle, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_include_max_depth() {
+	let mut source = MemorySource::new();
+	source.insert("config.toml", b"include = \"a.toml\"\n".to_vec());
+	source.insert("a.toml", b"include = \"b.toml\"\n".to_vec());
+	source.insert("b.toml", b"name = \"b\"\n".to_vec());
+
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.max_include_depth(1)
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::IncludeDepth(1)) => {}
+		other => panic!("Expected IncludeDepth, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_include_absolute() {
+	let mut source = MemorySource::new();
+	source.insert("app/config.toml", b"include = [\"/abs.toml\"]\n".to_vec());
+	source.insert("abs.toml", b"name = \"x\"\nport = 1\ndb = \"y\"\n".to_vec());
+
+	match loader(source).load::<TestConfig>("app/config").await {
+		LoadResult::Invalid(FmtError::InvalidPath(message)) => {
+			assert!(message.contains("`/abs.toml`"), "{}", message);
+		}
+		other => panic!("Expected InvalidPath, got {:?}", other),
+	}
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn test_include_outside_sandbox() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let root = tmp_dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	std::fs::write(tmp_dir.path().join("secret.toml"), "name = \"x\"\n").unwrap();
+	std::fs::write(root.join("config.toml"), "include = \"../secret.toml\"\n").unwrap();
+
+	let loader = DynLoader::builder()
+		.source(fmtstruct::FileSource::new(&root))
+		.format(AnyFormat::Toml)
+		.includes()
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::SandboxViolation) => {}
+		other => panic!("Expected SandboxViolation, got {:?}", other),
+	}
+}
@@ -0,0 +1,123 @@
+/* tests/interpolate_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, FmtError, Interpolator, LoadResult, MemorySource, PreProcess};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	server: Server,
+	url: String,
+	user: String,
+	template: String,
+}
+
+impl PreProcess for TestConfig {}
+
+fn loader(key: &str, content: &str, format: AnyFormat) -> DynLoader {
+	let mut source = MemorySource::new();
+	source.insert(key, content.as_bytes().to_vec());
+	DynLoader::builder()
+		.source(source)
+		.format(format)
+		.interpolate(Interpolator::new().vars([("HOST", "example.com"), ("PORT", "8443")]))
+		.build()
+		.unwrap()
+}
+
+#[tokio::test]
+async fn test_interpolate_json() {
+	let loader = loader(
+		"config.json",
+		r#"{
+			"server": { "host": "${HOST}", "port": "${env:PORT}" },
+			"url": "https://${server.host}:${server.port}/",
+			"user": "${env:USER_NAME:-guest}",
+			"template": "$${literal}"
+		}"#,
+		AnyFormat::Json,
+	);
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.server.host, "example.com");
+			assert_eq!(value.server.port, 8443);
+			assert_eq!(value.url, "https://example.com:8443/");
+			assert_eq!(value.user, "guest");
+			assert_eq!(value.template, "${literal}");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_interpolate_toml() {
+	let loader = loader(
+		"config.toml",
+		r#"url = "http://${server.host}"
+user = "${MISSING:-nobody}"
+template = "plain"
+
+[server]
+host = "${HOST}"
+port = 80
+"#,
+		AnyFormat::Toml,
+	);
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.url, "http://example.com");
+			assert_eq!(value.user, "nobody");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[cfg(feature = "yaml")]
+#[tokio::test]
+async fn test_interpolate_yaml() {
+	let loader = loader(
+		"config.yaml",
+		"server:\n  host: ${HOST}\n  port: 1\nurl: ${server.host}\nuser: x\ntemplate: y\n",
+		AnyFormat::Yaml,
+	);
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => assert_eq!(value.url, "example.com"),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_interpolate_undefined() {
+	let loader = loader(
+		"config.json",
+		r#"{
+			"server": { "host": "${NOPE}", "port": 1 },
+			"url": "", "user": "", "template": ""
+		}"#,
+		AnyFormat::Json,
+	);
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::Interpolation { file, key, message }) => {
+			assert_eq!(file, "config.json");
+			assert_eq!(key, "server.host");
+			assert!(message.contains("NOPE"), "{}", message);
+		}
+		other => panic!("Expected Interpolation error, got {:?}", other),
+	}
+}
@@ -0,0 +1,221 @@
+/* tests/jsonschema_tests.rs */
+
+#![cfg(all(feature = "jsonschema", feature = "toml", feature = "yaml"))]
+
+use fmtstruct::format::{AnyFormat, Yaml};
+use fmtstruct::{
+	DocumentSchema, DynLoader, EnvOverlay, FmtError, LoadResult, MemorySource, PreProcess,
+	SchemaViolation, StaticLoader,
+};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Server {
+	host: String,
+	port: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	servers: Vec<Server>,
+}
+
+impl PreProcess for TestConfig {}
+
+const SCHEMA: &str = r#"{
+	"type": "object",
+	"required": ["name", "servers"],
+	"properties": {
+		"name": { "type": "string", "minLength": 1 },
+		"servers": {
+			"type": "array",
+			"items": {
+				"type": "object",
+				"properties": {
+					"host": { "type": "string" },
+					"port": { "type": "integer", "minimum": 1, "maximum": 65535 }
+				}
+			}
+		}
+	}
+}"#;
+
+fn schema() -> DocumentSchema {
+	DocumentSchema::from_json(SCHEMA.as_bytes()).unwrap()
+}
+
+fn violations<T: core::fmt::Debug>(result: LoadResult<T>) -> Vec<SchemaViolation> {
+	match result {
+		LoadResult::Invalid(FmtError::Schema(violations)) => violations,
+		other => panic!("Expected Schema error, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_schema_dyn_loader() {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.toml",
+		br#"
+name = ""
+
+[[servers]]
+host = "a"
+port = 80
+
+[[servers]]
+host = "b"
+port = 70000
+"#
+		.to_vec(),
+	);
+	source.insert(
+		"valid.yaml",
+		b"name: app\nservers:\n  - host: a\n    port: 80\n".to_vec(),
+	);
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Yaml)
+		.schema(schema())
+		.build()
+		.unwrap();
+
+	let violations = violations(loader.load::<TestConfig>("config").await);
+	assert_eq!(violations.len(), 2);
+	assert_eq!(violations[0].path, "name");
+	assert_eq!(violations[1].path, "servers[1].port");
+	assert!(
+		violations[1].message.contains("70000"),
+		"{}",
+		violations[1].message
+	);
+
+	let error = FmtError::Schema(violations);
+	assert!(
+		error.to_string().contains("; servers[1].port: "),
+		"{}",
+		error
+	);
+
+	match loader.load::<TestConfig>("valid").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.name, "app");
+			assert_eq!(value.servers[0].host, "a");
+			assert_eq!(value.servers[0].port, 80);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_schema_static_loader() {
+	let mut source = MemorySource::new();
+	source.insert("config.yaml", b"servers:\n  - port: 0\n".to_vec());
+	let loader = StaticLoader::new(source, Yaml).with_schema(schema());
+
+	let violations = violations(loader.load::<TestConfig>("config.yaml").await);
+	assert_eq!(
+		violations
+			.iter()
+			.map(|v| v.path.as_str())
+			.collect::<Vec<_>>(),
+		["", "servers[0].port"]
+	);
+	assert!(violations[0].message.contains("name"));
+}
+
+#[tokio::test]
+async fn test_schema_coerces_env_and_overrides() {
+	#[derive(Debug, Deserialize)]
+	#[cfg_attr(feature = "validate", derive(Validate))]
+	struct Listener {
+		port: u16,
+		tls: bool,
+	}
+	impl PreProcess for Listener {}
+
+	let schema = || {
+		DocumentSchema::from_json(
+			br#"{"properties": {"port": {"type": "integer", "maximum": 65535}, "tls": {"type": "boolean"}}}"#,
+		)
+		.unwrap()
+	};
+	let source = || {
+		let mut source = MemorySource::new();
+		source.insert("listener.yaml", b"port: 80\ntls: false\n".to_vec());
+		source
+	};
+
+	let env = EnvOverlay::new("APP").vars([("APP__PORT", "8080"), ("APP__TLS", "true")]);
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Yaml)
+		.env(env)
+		.schema(schema())
+		.build()
+		.unwrap();
+	match loader.load::<Listener>("listener").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.port, 8080);
+			assert!(value.tls);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	let loader = StaticLoader::new(source(), Yaml)
+		.with_override("port", "9000")
+		.with_schema(schema());
+	match loader.load::<Listener>("listener.yaml").await {
+		LoadResult::Ok { value, .. } => assert_eq!(value.port, 9000),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	// Coerced values are still checked against the schema
+	let loader = StaticLoader::new(source(), Yaml)
+		.with_override("port", "70000")
+		.with_schema(schema());
+	let out_of_range = violations(loader.load::<Listener>("listener.yaml").await);
+	assert_eq!(out_of_range.len(), 1);
+	assert_eq!(out_of_range[0].path, "port");
+	assert!(
+		out_of_range[0].message.contains("70000"),
+		"{}",
+		out_of_range[0].message
+	);
+
+	// Strings read from the file itself are not coerced
+	let mut source = source();
+	source.insert("listener.yaml", b"port: \"80\"\ntls: false\n".to_vec());
+	let loader = StaticLoader::new(source, Yaml).with_schema(schema());
+	assert_eq!(
+		violations(loader.load::<Listener>("listener.yaml").await)[0].path,
+		"port"
+	);
+}
+
+#[cfg(feature = "schema")]
+#[tokio::test]
+async fn test_schema_from_type() {
+	#[derive(Debug, Deserialize, fmtstruct::schema::JsonSchema)]
+	#[cfg_attr(feature = "validate", derive(Validate))]
+	struct Typed {
+		#[cfg_attr(feature = "validate", validate(range(min = 1)))]
+		workers: u32,
+	}
+	impl PreProcess for Typed {}
+
+	let mut source = MemorySource::new();
+	source.insert("config.yaml", b"workers: 0\nextra: true\n".to_vec());
+	let loader = StaticLoader::new(source, Yaml).with_schema(DocumentSchema::of::<Typed>().unwrap());
+
+	let violations = violations(loader.load::<Typed>("config.yaml").await);
+	assert_eq!(violations.len(), 1);
+	assert_eq!(violations[0].path, "workers");
+}
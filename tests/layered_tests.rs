@@ -0,0 +1,214 @@
+/* tests/layered_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{Layer, LayeredLoader, LoadResult, MemorySource, PreProcess};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	server: Server,
+	#[serde(default)]
+	tags: Vec<String>,
+}
+
+impl PreProcess for TestConfig {}
+
+fn source(key: &str, content: &str) -> MemorySource {
+	let mut source = MemorySource::new();
+	source.insert(key, content.as_bytes().to_vec());
+	source
+}
+
+#[tokio::test]
+async fn test_layers_deep_merge() {
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(
+			source(
+				"defaults.json",
+				r#"{ "name": "app", "server": { "host": "0.0.0.0", "port": 80 }, "tags": ["a"] }"#,
+			),
+			"defaults.json",
+			AnyFormat::Json,
+		))
+		.layer(Layer::new(
+			source(
+				"user.toml",
+				"tags = [\"b\", \"c\"]\n[server]\nport = 8080\n",
+			),
+			"user.toml",
+			AnyFormat::Toml,
+		))
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>().await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.name, "app");
+			assert_eq!(value.server.host, "0.0.0.0");
+			assert_eq!(value.server.port, 8080);
+			// Arrays are replaced, not concatenated
+			assert_eq!(value.tags, vec!["b", "c"]);
+			assert_eq!(info.path, PathBuf::from("user.toml"));
+			assert_eq!(info.format, "toml");
+			assert_eq!(
+				info.layers,
+				vec![PathBuf::from("defaults.json"), PathBuf::from("user.toml")]
+			);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_missing_optional_layer_is_skipped() {
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(
+			source(
+				"defaults.json",
+				r#"{ "name": "app", "server": { "host": "localhost", "port": 80 } }"#,
+			),
+			"defaults.json",
+			AnyFormat::Json,
+		))
+		.layer(Layer::new(
+			MemorySource::new(),
+			"local.toml",
+			AnyFormat::Toml,
+		))
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>().await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.server.port, 80);
+			assert_eq!(info.layers, vec![PathBuf::from("defaults.json")]);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_missing_required_layer() {
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(MemorySource::new(), "system.toml", AnyFormat::Toml).required())
+		.build()
+		.unwrap();
+
+	assert!(matches!(
+		loader.load::<TestConfig>().await,
+		LoadResult::NotFound
+	));
+}
+
+#[tokio::test]
+async fn test_type_error_names_layer() {
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(
+			source(
+				"defaults.json",
+				r#"{ "name": "app", "server": { "host": "localhost", "port": 80 } }"#,
+			),
+			"defaults.json",
+			AnyFormat::Json,
+		))
+		.layer(Layer::new(
+			source("user.toml", "[server]\nport = \"http\"\n"),
+			"user.toml",
+			AnyFormat::Toml,
+		))
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>().await {
+		LoadResult::Invalid(fmtstruct::FmtError::ParseError(e)) => {
+			assert_eq!(e.path.as_deref(), Some("server.port"));
+			assert_eq!(e.key.as_deref(), Some("user.toml"));
+			assert_eq!(e.format, Some("toml"));
+			assert_eq!((e.line, e.column), (Some(2), Some(8)));
+			assert_eq!(e.span, Some(16..22));
+			assert_eq!(e.source_line.as_deref(), Some("port = \"http\""));
+			assert!(
+				e.to_string()
+					.ends_with(" --> user.toml:2:8 (toml)\n  |\n2 | port = \"http\"\n  |        ^^^^^^"),
+				"{}",
+				e
+			);
+		}
+		other => panic!("Expected Invalid(ParseError), got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_integer_map_keys() {
+	#[derive(Debug, Deserialize)]
+	#[cfg_attr(feature = "validate", derive(Validate))]
+	struct Routes {
+		ports: HashMap<u32, String>,
+		weights: BTreeMap<i8, bool>,
+	}
+
+	impl PreProcess for Routes {}
+
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(
+			source(
+				"defaults.json",
+				r#"{ "ports": { "80": "http" }, "weights": { "-1": false } }"#,
+			),
+			"defaults.json",
+			AnyFormat::Json,
+		))
+		.layer(Layer::new(
+			source("user.toml", "[ports]\n443 = \"https\"\n"),
+			"user.toml",
+			AnyFormat::Toml,
+		))
+		.build()
+		.unwrap();
+
+	match loader.load::<Routes>().await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(
+				value.ports,
+				HashMap::from([(80, "http".to_string()), (443, "https".to_string())])
+			);
+			assert_eq!(value.weights, BTreeMap::from([(-1, false)]));
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(
+			source("user.toml", "[ports]\nhttp = \"x\"\n[weights]\n"),
+			"user.toml",
+			AnyFormat::Toml,
+		))
+		.build()
+		.unwrap();
+	match loader.load::<Routes>().await {
+		LoadResult::Invalid(e) => {
+			assert!(
+				e.to_string()
+					.contains("invalid type: string \"http\", expected u32"),
+				"{}",
+				e
+			);
+		}
+		other => panic!("Expected Invalid, got {:?}", other),
+	}
+}
@@ -78,8 +78,7 @@ async fn test_dyn_loader_auto_detect() {
 		LoadResult::Ok { value: cfg, .. } => {
 			assert_eq!(cfg.name, "test");
 			assert_eq!(cfg.value, 42);
-			// Context is no longer set automatically by load()
-			assert_eq!(cfg.context, "");
+			assert_eq!(cfg.context, "config.json");
 		}
 		_ => panic!("Expected Ok result, got {:?}", result),
 	}
@@ -98,8 +97,7 @@ async fn test_dyn_loader_load_file() {
 		LoadResult::Ok { value: cfg, .. } => {
 			assert_eq!(cfg.name, "file");
 			assert_eq!(cfg.value, 10);
-			// Context is no longer set automatically by load_file()
-			assert_eq!(cfg.context, "");
+			assert_eq!(cfg.context, "specific.json");
 		}
 		_ => panic!("Expected Ok result, got {:?}", result),
 	}
@@ -127,7 +125,11 @@ async fn test_parse_error() {
 	let result: LoadResult<TestConfig> = loader.load("config").await;
 	match result {
 		#[cfg(feature = "alloc")]
-		LoadResult::Invalid(FmtError::ParseError(_)) => {}
+		LoadResult::Invalid(FmtError::ParseError(e)) => {
+			assert_eq!(e.key.as_deref(), Some("config.json"));
+			assert_eq!(e.format, Some("json"));
+			assert_eq!((e.line, e.column), (Some(1), Some(1)));
+		}
 		#[cfg(not(feature = "alloc"))]
 		LoadResult::Invalid(FmtError::ParseError) => {}
 		_ => panic!("Expected Invalid(ParseError), got {:?}", result),
@@ -0,0 +1,101 @@
+/* tests/override_tests.rs */
+
+#![cfg(feature = "json")]
+
+use fmtstruct::format::{AnyFormat, Json};
+use fmtstruct::value::to_value;
+use fmtstruct::{DynLoader, FmtError, LoadResult, MemorySource, PreProcess, StaticLoader};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	servers: Vec<Server>,
+	tags: Vec<String>,
+}
+
+impl PreProcess for TestConfig {}
+
+fn source() -> MemorySource {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.json",
+		r#"{ "servers": [{ "host": "a", "port": 80 }, { "host": "b", "port": 81 }], "tags": [] }"#
+			.as_bytes()
+			.to_vec(),
+	);
+	source
+}
+
+#[tokio::test]
+async fn test_dyn_loader_overrides() {
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.with_override("servers[1].port", "9000")
+		.with_override("servers[0].host", "example.com")
+		.with_override("tags", to_value(&["x", "y"]).unwrap())
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.servers[0].host, "example.com");
+			assert_eq!(value.servers[0].port, 80);
+			assert_eq!(value.servers[1].port, 9000);
+			assert_eq!(value.tags, vec!["x", "y"]);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_override_missing_path() {
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.with_override("servers[5].port", 1)
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::PathNotFound(path)) => assert_eq!(path, "servers[5].port"),
+		other => panic!("Expected PathNotFound, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_override_invalid_path() {
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Json)
+		.with_override("servers[x]", 1)
+		.build()
+		.unwrap();
+
+	let result = loader.load::<TestConfig>("config").await;
+	assert!(
+		matches!(result, LoadResult::Invalid(FmtError::InvalidPath(_))),
+		"{:?}",
+		result
+	);
+}
+
+#[tokio::test]
+async fn test_static_loader_overrides() {
+	let loader = StaticLoader::new(source(), Json).with_override("servers[0].port", 8080);
+
+	match loader.load::<TestConfig>("config.json").await {
+		LoadResult::Ok { value, .. } => assert_eq!(value.servers[0].port, 8080),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
@@ -0,0 +1,289 @@
+/* tests/poll_tests.rs */
+
+#![cfg(all(feature = "reload", feature = "json"))]
+
+use async_trait::async_trait;
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{AsyncPreProcess, ConfigHandle, DynLoader, FmtError, Poller, PreProcess, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	#[cfg_attr(feature = "validate", validate(range(min = 1)))]
+	port: u16,
+}
+
+impl PreProcess for TestConfig {}
+
+/// A source whose content can change after the loader takes ownership of it.
+#[derive(Clone, Default)]
+struct SharedSource(Arc<Mutex<BTreeMap<String, Vec<u8>>>>);
+
+impl SharedSource {
+	fn set(&self, key: &str, value: impl AsRef<[u8]>) {
+		self
+			.0
+			.lock()
+			.unwrap()
+			.insert(key.to_string(), value.as_ref().to_vec());
+	}
+}
+
+#[async_trait]
+impl Source for SharedSource {
+	async fn read(&self, key: &str) -> Result<Vec<u8>, FmtError> {
+		self
+			.0
+			.lock()
+			.unwrap()
+			.get(key)
+			.cloned()
+			.ok_or(FmtError::NotFound)
+	}
+
+	async fn exists(&self, key: &str) -> bool {
+		self.0.lock().unwrap().contains_key(key)
+	}
+}
+
+fn loader(source: &SharedSource) -> DynLoader {
+	DynLoader::builder()
+		.source(source.clone())
+		.format(AnyFormat::Json)
+		.profile("prod")
+		.build()
+		.unwrap()
+}
+
+async fn changed<T>(handle: &mut ConfigHandle<T>, within: Duration) -> bool {
+	tokio::time::timeout(within, handle.changed()).await.is_ok()
+}
+
+async fn recv(errors: &mut tokio::sync::mpsc::UnboundedReceiver<String>) -> String {
+	tokio::time::timeout(Duration::from_secs(5), errors.recv())
+		.await
+		.expect("timed out waiting for error")
+		.unwrap()
+}
+
+#[tokio::test]
+async fn test_poll_detects_changes() {
+	let source = SharedSource::default();
+	source.set("config.json", r#"{ "port": 1 }"#);
+
+	let mut handle = Poller::new(loader(&source), "config")
+		.interval(Duration::from_millis(10))
+		.jitter(Duration::from_millis(5))
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+	assert_eq!(handle.get().port, 1);
+
+	// Identical bytes are not re-published.
+	source.set("config.json", r#"{ "port": 1 }"#);
+	assert!(!changed(&mut handle, Duration::from_millis(100)).await);
+
+	source.set("config.json", r#"{ "port": 2 }"#);
+	assert!(changed(&mut handle, Duration::from_secs(5)).await);
+	assert_eq!(handle.get().port, 2);
+
+	// A profile overlay that did not exist at start is picked up.
+	source.set("config.prod.json", r#"{ "port": 3 }"#);
+	assert!(changed(&mut handle, Duration::from_secs(5)).await);
+	assert_eq!(handle.get().port, 3);
+}
+
+#[tokio::test]
+async fn test_poll_reload_now() {
+	let source = SharedSource::default();
+	source.set("config.json", r#"{ "port": 1 }"#);
+
+	let handle = Poller::new(loader(&source), "config")
+		.interval(Duration::from_secs(3600))
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+
+	source.set("config.json", r#"{ "port": 2 }"#);
+	handle.reload_now().await.unwrap();
+	assert_eq!(handle.get().port, 2);
+
+	source.set("config.json", r#"{ "port": "#);
+	assert!(handle.reload_now().await.is_err());
+	assert_eq!(handle.get().port, 2);
+}
+
+#[tokio::test]
+async fn test_poll_last_known_good() {
+	let source = SharedSource::default();
+	source.set("config.json", r#"{ "port": 1 }"#);
+
+	let (errors_tx, mut errors) = tokio::sync::mpsc::unbounded_channel();
+	let mut handle = Poller::new(loader(&source), "config")
+		.interval(Duration::from_millis(10))
+		.on_error(move |e| errors_tx.send(e.to_string()).unwrap())
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+
+	source.set("config.json", r#"{ "port": "#);
+	let message = recv(&mut errors).await;
+	assert!(!message.is_empty());
+	assert!(handle.error().is_some());
+	assert_eq!(handle.get().port, 1);
+
+	#[cfg(feature = "validate")]
+	{
+		source.set("config.json", r#"{ "port": 0 }"#);
+		recv(&mut errors).await;
+		assert!(matches!(
+			handle.error().as_deref(),
+			Some(FmtError::Validation(_))
+		));
+		assert_eq!(handle.get().port, 1);
+	}
+
+	source.set("config.json", r#"{ "port": 2 }"#);
+	assert!(changed(&mut handle, Duration::from_secs(5)).await);
+	assert_eq!(handle.get().port, 2);
+	assert!(handle.error().is_none());
+}
+
+#[tokio::test]
+async fn test_poll_diff() {
+	#[derive(Debug, Deserialize, Serialize)]
+	#[cfg_attr(feature = "validate", derive(Validate))]
+	struct DbConfig {
+		port: u16,
+		password: String,
+		#[serde(default = "default_timeout")]
+		timeout: u16,
+	}
+
+	fn default_timeout() -> u16 {
+		30
+	}
+
+	impl PreProcess for DbConfig {}
+
+	let source = SharedSource::default();
+	source.set("config.json", r#"{ "port": 1, "password": "a" }"#);
+
+	let mut handle = Poller::new(loader(&source), "config")
+		.interval(Duration::from_millis(10))
+		.secret("password")
+		.start::<DbConfig>()
+		.await
+		.unwrap();
+	assert!(handle.diff().is_empty());
+
+	source.set("config.json", r#"{ "port": 2, "password": "b" }"#);
+	let diff = tokio::time::timeout(Duration::from_secs(5), handle.changed())
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(handle.get().port, 2);
+	assert_eq!(handle.get().password, "b");
+	assert!(diff.touches("port"));
+
+	let rendered = format!("{:?}", diff);
+	assert!(rendered.contains("[redacted]"), "{}", rendered);
+	assert!(!rendered.contains("\"b\""), "{}", rendered);
+
+	// Formatting-only changes produce an equal value and are not published,
+	// nor is spelling out a default.
+	source.set("config.json", r#"{"port":2,"password":"b"}"#);
+	assert!(!changed(&mut handle, Duration::from_millis(100)).await);
+	source.set("config.json", r#"{"port":2,"password":"b","timeout":30}"#);
+	assert!(!changed(&mut handle, Duration::from_millis(100)).await);
+
+	source.set("config.json", r#"{"port":2,"password":"b","timeout":60}"#);
+	let diff = tokio::time::timeout(Duration::from_secs(5), handle.changed())
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(
+		diff.changes().iter().map(|c| c.path()).collect::<Vec<_>>(),
+		["timeout"]
+	);
+}
+
+#[cfg(feature = "postcard")]
+#[tokio::test]
+async fn test_poll_diff_without_document() {
+	use fmtstruct::Format;
+	use fmtstruct::format::Style;
+
+	let encode = |port: u16| {
+		AnyFormat::Postcard
+			.serialize(&TestConfig { port }, Style::Compact)
+			.unwrap()
+	};
+	let source = SharedSource::default();
+	source.set("config.bin", encode(1));
+
+	let loader = DynLoader::builder()
+		.source(source.clone())
+		.format(AnyFormat::Postcard)
+		.build()
+		.unwrap();
+	let mut handle = Poller::new(loader, "config")
+		.interval(Duration::from_millis(10))
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+
+	source.set("config.bin", encode(2));
+	let diff = tokio::time::timeout(Duration::from_secs(5), handle.changed())
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(handle.get().port, 2);
+	assert!(diff.touches("port"));
+}
+
+#[tokio::test]
+async fn test_reload_now_publishes_unchanged_document() {
+	#[derive(Debug, Deserialize, Serialize)]
+	#[cfg_attr(feature = "validate", derive(Validate))]
+	struct TlsConfig {
+		cert_file: String,
+		#[serde(skip)]
+		cert: Vec<u8>,
+	}
+
+	#[async_trait]
+	impl AsyncPreProcess for TlsConfig {
+		async fn pre_process_async(&mut self, source: &dyn Source) -> Result<(), FmtError> {
+			self.cert = source.read(&self.cert_file).await?;
+			Ok(())
+		}
+	}
+
+	let source = SharedSource::default();
+	source.set("config.json", r#"{ "cert_file": "cert.pem" }"#);
+	source.set("cert.pem", "old");
+
+	let mut handle = Poller::new(loader(&source), "config")
+		.interval(Duration::from_secs(3600))
+		.start::<TlsConfig>()
+		.await
+		.unwrap();
+	assert_eq!(handle.get().cert, b"old");
+
+	source.set("cert.pem", "new");
+	handle.reload_now().await.unwrap();
+	let diff = tokio::time::timeout(Duration::from_secs(5), handle.changed())
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(handle.get().cert, b"new");
+	assert!(diff.is_empty());
+}
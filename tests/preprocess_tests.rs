@@ -4,9 +4,13 @@
 
 use async_trait::async_trait;
 use fmtstruct::format::AnyFormat;
-use fmtstruct::{DynLoader, FmtError, LoadResult, PreProcess, Source};
+use fmtstruct::format::Json;
+use fmtstruct::{
+	AsyncPreProcess, DynLoader, FmtError, LoadResult, PreProcess, Source, StaticLoader, TryPreProcess,
+};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 #[derive(Default)]
 struct MockSource {
@@ -51,14 +55,229 @@ async fn test_preprocess_hooks() {
 	let loader = DynLoader::new(Box::new(source), vec![AnyFormat::Json]);
 	let result: LoadResult<TestConfig> = loader.load("config").await;
 	match result {
-		LoadResult::Ok { mut value, .. } => {
+		LoadResult::Ok { value, .. } => {
 			assert!(value.processed);
-			// set_context is no longer called automatically
-			assert_eq!(value.ctx, "");
-			// Verify we can call it manually
-			value.set_context("config.json");
 			assert_eq!(value.ctx, "config.json");
 		}
 		_ => panic!("Expected Ok, got {:?}", result),
 	}
 }
+
+#[tokio::test]
+async fn test_skip_preprocess_hooks() {
+	let mut data = HashMap::new();
+	data.insert("config.json".to_string(), r#"{}"#.as_bytes().to_vec());
+	let loader = DynLoader::builder()
+		.source(MockSource { data })
+		.format(AnyFormat::Json)
+		.skip_context()
+		.skip_pre_process()
+		.build()
+		.unwrap();
+	let result: LoadResult<TestConfig> = loader.load("config").await;
+	match result {
+		LoadResult::Ok { value, .. } => {
+			assert!(!value.processed);
+			assert_eq!(value.ctx, "");
+		}
+		_ => panic!("Expected Ok, got {:?}", result),
+	}
+}
+
+#[cfg(all(feature = "fs", feature = "toml"))]
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct CertConfig {
+	cert: std::path::PathBuf,
+	#[serde(skip)]
+	context: Option<fmtstruct::LoadContext>,
+}
+
+#[cfg(all(feature = "fs", feature = "toml"))]
+impl PreProcess for CertConfig {
+	fn set_load_context(&mut self, ctx: &fmtstruct::LoadContext) {
+		self.context = Some(ctx.clone());
+	}
+
+	fn pre_process(&mut self) {
+		if let Some(ctx) = &self.context {
+			self.cert = ctx.config_dir.join(&self.cert);
+		}
+	}
+}
+
+#[cfg(all(feature = "fs", feature = "toml"))]
+#[tokio::test]
+async fn test_load_context() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let root = tmp_dir.path();
+	std::fs::create_dir(root.join("conf")).unwrap();
+	std::fs::write(root.join("conf/app.json"), r#"{ "cert": "tls/cert.pem" }"#).unwrap();
+	std::fs::write(root.join("conf/app.toml"), "cert = \"other.pem\"\n").unwrap();
+	std::fs::write(root.join("conf/app.prod.json"), "{}").unwrap();
+
+	let loader = DynLoader::builder()
+		.source(fmtstruct::FileSource::new(root))
+		.format(AnyFormat::Json)
+		.format(AnyFormat::Toml)
+		.profile("prod")
+		.build()
+		.unwrap();
+	match loader.load::<CertConfig>("conf/app").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.cert, root.join("conf/tls/cert.pem"));
+			let ctx = value.context.unwrap();
+			assert_eq!(ctx.key, "conf/app.json");
+			assert_eq!(ctx.path, root.join("conf/app.json"));
+			assert_eq!(ctx.format, "json");
+			assert_eq!(ctx.source_root.as_deref(), Some(root));
+			assert_eq!(ctx.profile.as_deref(), Some("prod"));
+			assert_eq!(ctx.config_dir, root.join("conf"));
+			assert_eq!(ctx.conflicts, [std::path::PathBuf::from("conf/app.toml")]);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct PortRange {
+	min: u16,
+	max: u16,
+}
+
+impl TryPreProcess for PortRange {
+	fn try_pre_process(&mut self) -> Result<(), FmtError> {
+		if self.min > self.max {
+			return Err(FmtError::PreProcess(format!(
+				"`min` ({}) exceeds `max` ({})",
+				self.min, self.max
+			)));
+		}
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_try_preprocess() {
+	let mut data = HashMap::new();
+	data.insert(
+		"ok.json".to_string(),
+		r#"{ "min": 80, "max": 90 }"#.as_bytes().to_vec(),
+	);
+	data.insert(
+		"config.json".to_string(),
+		r#"{ "min": 90, "max": 80 }"#.as_bytes().to_vec(),
+	);
+
+	let loader = StaticLoader::new(MockSource { data: data.clone() }, Json);
+	match loader.load::<PortRange>("ok.json").await {
+		LoadResult::Ok { value, .. } => assert_eq!((value.min, value.max), (80, 90)),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+	match loader.load::<PortRange>("config.json").await {
+		LoadResult::Invalid(FmtError::PreProcess(msg)) => {
+			assert_eq!(msg, "`min` (90) exceeds `max` (80)");
+		}
+		other => panic!("Expected Invalid(PreProcess), got {:?}", other),
+	}
+
+	let loader = DynLoader::new(Box::new(MockSource { data }), vec![AnyFormat::Json]);
+	let result: LoadResult<PortRange> = loader.load("config").await;
+	assert!(
+		matches!(result, LoadResult::Invalid(FmtError::PreProcess(_))),
+		"Expected Invalid(PreProcess), got {:?}",
+		result
+	);
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TlsConfig {
+	cert_file: String,
+	#[serde(skip)]
+	cert: Vec<u8>,
+	#[serde(skip)]
+	dir: String,
+}
+
+#[async_trait]
+impl AsyncPreProcess for TlsConfig {
+	async fn pre_process_async(&mut self, source: &dyn Source) -> Result<(), FmtError> {
+		self.cert = source
+			.read(&format!("{}{}", self.dir, self.cert_file))
+			.await?;
+		Ok(())
+	}
+
+	fn set_load_context(&mut self, ctx: &fmtstruct::LoadContext) {
+		self.dir = ctx.key[..ctx.key.rfind('/').map_or(0, |i| i + 1)].to_string();
+	}
+}
+
+#[tokio::test]
+async fn test_async_preprocess() {
+	let mut data = HashMap::new();
+	data.insert(
+		"tls/config.json".to_string(),
+		r#"{ "cert_file": "cert.pem" }"#.as_bytes().to_vec(),
+	);
+	data.insert("tls/cert.pem".to_string(), b"-----BEGIN".to_vec());
+	data.insert(
+		"broken.json".to_string(),
+		r#"{ "cert_file": "missing.pem" }"#.as_bytes().to_vec(),
+	);
+
+	let loader = DynLoader::new(
+		Box::new(MockSource { data: data.clone() }),
+		vec![AnyFormat::Json],
+	);
+	match loader.load_with_source::<TlsConfig>("tls/config").await {
+		LoadResult::Ok { value, .. } => {
+			assert_eq!(value.cert_file, "cert.pem");
+			assert_eq!(value.cert, b"-----BEGIN");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	let loader = StaticLoader::new(MockSource { data }, Json);
+	match loader.load_with_source::<TlsConfig>("broken.json").await {
+		LoadResult::Invalid(FmtError::NotFound) => {}
+		other => panic!("Expected Invalid(NotFound), got {:?}", other),
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Shared {
+	name: String,
+	#[serde(skip)]
+	shared: Rc<String>,
+}
+
+impl PreProcess for Shared {
+	fn pre_process(&mut self) {
+		self.shared = Rc::new(self.name.clone());
+	}
+}
+
+#[tokio::test]
+async fn test_preprocess_without_send() {
+	let mut data = HashMap::new();
+	data.insert(
+		"config.json".to_string(),
+		r#"{ "name": "shared" }"#.as_bytes().to_vec(),
+	);
+
+	let loader = StaticLoader::new(MockSource { data: data.clone() }, Json);
+	match loader.load::<Shared>("config.json").await {
+		LoadResult::Ok { value, .. } => assert_eq!(*value.shared, "shared"),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	let loader = DynLoader::new(Box::new(MockSource { data }), vec![AnyFormat::Json]);
+	match loader.load::<Shared>("config").await {
+		LoadResult::Ok { value, .. } => assert_eq!(*value.shared, "shared"),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
@@ -0,0 +1,101 @@
+/* tests/profile_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, LoadResult, MemorySource, PreProcess};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	port: u16,
+	debug: bool,
+}
+
+impl PreProcess for TestConfig {}
+
+fn source() -> MemorySource {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.toml",
+		b"name = \"app\"\nport = 80\ndebug = false\n".to_vec(),
+	);
+	source.insert("config.dev.json", br#"{ "debug": true }"#.to_vec());
+	source.insert("config.prod.toml", b"port = 443\n".to_vec());
+	source
+}
+
+#[tokio::test]
+async fn test_explicit_profile() {
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.profile("dev")
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.name, "app");
+			assert_eq!(value.port, 80);
+			assert!(value.debug);
+			assert_eq!(info.profile.as_deref(), Some("dev"));
+			assert_eq!(info.path, PathBuf::from("config.toml"));
+			assert_eq!(
+				info.layers,
+				vec![
+					PathBuf::from("config.toml"),
+					PathBuf::from("config.dev.json")
+				]
+			);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_profile_from_env() {
+	// SAFETY: the variable name is unique to this test.
+	unsafe { std::env::set_var("FMTSTRUCT_TEST_PROFILE", "prod") };
+
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Toml)
+		.profile_env("FMTSTRUCT_TEST_PROFILE")
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.port, 443);
+			assert_eq!(info.profile.as_deref(), Some("prod"));
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_missing_profile_file() {
+	let loader = DynLoader::builder()
+		.source(source())
+		.format(AnyFormat::Toml)
+		.profile("staging")
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.port, 80);
+			assert_eq!(info.profile, None);
+			assert_eq!(info.layers, vec![PathBuf::from("config.toml")]);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
@@ -0,0 +1,240 @@
+/* tests/provenance_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{
+	DynLoader, EnvOverlay, FmtError, Format, Layer, LayeredLoader, LoadResult, MemorySource,
+	OriginKind, PreProcess, StaticLoader,
+};
+use serde::Deserialize;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	port: u16,
+	#[serde(default)]
+	timeout: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	server: Server,
+	tags: Vec<String>,
+}
+
+impl PreProcess for TestConfig {}
+
+fn loader() -> DynLoader {
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.toml",
+		b"name = \"app\"\ntags = [\"a\", \"b\"]\n\n[server]\nhost = \"localhost\"\nport = 80\n"
+			.to_vec(),
+	);
+	source.insert("config.prod.json", br#"{ "name": "prod" }"#.to_vec());
+
+	DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.profile("prod")
+		.env(EnvOverlay::new("APP").vars([("APP__SERVER__PORT", "8080")]))
+		.with_override("server.host", "example.com")
+		.build()
+		.unwrap()
+}
+
+#[tokio::test]
+async fn test_origins_in_load_info() {
+	match loader().load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.name, "prod");
+			assert_eq!(value.server.host, "example.com");
+			assert_eq!(value.server.port, 8080);
+			assert_eq!(value.server.timeout, 0);
+			assert_eq!(value.tags, ["a", "b"]);
+
+			let name = info.explain("name");
+			assert_eq!(name.kind, OriginKind::File);
+			assert_eq!(name.source, "config.prod.json");
+			assert_eq!(name.format, Some("json"));
+
+			let tags = info.explain("tags[1]");
+			assert_eq!(tags.kind, OriginKind::File);
+			assert_eq!(tags.source, "config.toml");
+			assert_eq!(tags.format, Some("toml"));
+			assert_eq!((tags.line, tags.column), (Some(2), Some(8)));
+
+			let port = info.explain("server.port");
+			assert_eq!(port.kind, OriginKind::Env);
+			assert_eq!(port.source, "APP__SERVER__PORT");
+
+			let host = info.explain("server.host");
+			assert_eq!(host.kind, OriginKind::Override);
+			assert_eq!(host.source, "server.host");
+
+			assert_eq!(info.explain("server.timeout").kind, OriginKind::Default);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_loader_explain() {
+	let origin = loader().explain("config", "server.port").await.unwrap();
+	assert_eq!(origin.kind, OriginKind::Env);
+}
+
+#[tokio::test]
+async fn test_layered_origins() {
+	let mut defaults = MemorySource::new();
+	defaults.insert(
+		"defaults.json",
+		br#"{ "name": "app", "server": { "host": "h", "port": 1 }, "tags": [] }"#.to_vec(),
+	);
+	let mut user = MemorySource::new();
+	user.insert("user.toml", b"[server]\nport = 2\n".to_vec());
+
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(defaults, "defaults.json", AnyFormat::Json))
+		.layer(Layer::new(user, "user.toml", AnyFormat::Toml))
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>().await {
+		LoadResult::Ok { info, .. } => {
+			assert_eq!(info.explain("server.host").source, "defaults.json");
+			let port = info.explain("server.port");
+			assert_eq!(port.source, "user.toml");
+			assert_eq!(port.line, Some(2));
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_merged_type_error_shows_line() {
+	let mut source = MemorySource::new();
+	source.insert("config.toml", b"name = \"app\"\ntags = []\n".to_vec());
+	source.insert(
+		"config.prod.toml",
+		b"[server]\nhost = \"h\"\nport = \"http\"\n".to_vec(),
+	);
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.profile("prod")
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::ParseError(e)) => {
+			assert_eq!(e.key.as_deref(), Some("config.prod.toml"));
+			assert_eq!((e.line, e.column), (Some(3), Some(8)));
+			assert_eq!(e.span, Some(27..33));
+			assert_eq!(e.source_line.as_deref(), Some("port = \"http\""));
+		}
+		other => panic!("Expected Invalid(ParseError), got {:?}", other),
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Limits {
+	id: u64,
+	port: u16,
+}
+
+impl PreProcess for Limits {}
+
+#[tokio::test]
+async fn test_single_file_parsed_directly() {
+	let mut source = MemorySource::new();
+	source.insert(
+		"max.json",
+		br#"{ "id": 18446744073709551615, "port": 80 }"#.to_vec(),
+	);
+	source.insert("text.toml", b"id = 1\nport = \"8080\"\n".to_vec());
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Json)
+		.format(AnyFormat::Toml)
+		.build()
+		.unwrap();
+
+	match loader.load::<Limits>("max").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.id, u64::MAX);
+			assert_eq!(value.port, 80);
+			// Origins are still recorded for the file
+			assert_eq!(info.explain("id").source, "max.json");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	// Strings in a file are not coerced to the field type
+	match loader.load::<Limits>("text").await {
+		LoadResult::Invalid(FmtError::ParseError(e)) => {
+			assert_eq!(e.path.as_deref(), Some("port"));
+		}
+		other => panic!("Expected ParseError, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_unsigned_integers_through_document() {
+	let mut source = MemorySource::new();
+	source.insert("max.json", br#"{ "id": 1, "port": 80 }"#.to_vec());
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Json)
+		.with_override("id", u64::MAX)
+		.build()
+		.unwrap();
+
+	match loader.load::<Limits>("max").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.id, u64::MAX);
+			assert_eq!(value.port, 80);
+			assert_eq!(info.explain("id").kind, OriginKind::Override);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+/// A format that only supports typed parsing, like most third-party formats.
+struct Typed;
+
+impl Format for Typed {
+	fn extensions(&self) -> &'static [&'static str] {
+		&["typed"]
+	}
+
+	fn parse<T: serde::de::DeserializeOwned>(&self, input: &[u8]) -> Result<T, FmtError> {
+		serde_json::from_slice(input).map_err(|e| fmtstruct::ParseError::new(e.to_string()).into())
+	}
+}
+
+#[tokio::test]
+async fn test_format_not_self_describing_by_default() {
+	assert!(!Typed.self_describing());
+
+	let mut source = MemorySource::new();
+	source.insert("limits.typed", br#"{ "id": 1, "port": 80 }"#.to_vec());
+	match StaticLoader::new(source, Typed)
+		.load::<Limits>("limits.typed")
+		.await
+	{
+		LoadResult::Ok { value, info } => {
+			assert_eq!((value.id, value.port), (1, 80));
+			assert!(info.origins.is_empty());
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
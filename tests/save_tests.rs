@@ -0,0 +1,90 @@
+/* tests/save_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{DynLoader, FmtError, LoadResult, MemorySource, PreProcess, Source};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	port: u16,
+}
+
+impl PreProcess for TestConfig {}
+
+fn config() -> TestConfig {
+	TestConfig {
+		name: "app".to_string(),
+		port: 8080,
+	}
+}
+
+#[tokio::test]
+async fn test_save_by_extension() {
+	let loader = DynLoader::builder()
+		.writable_source(MemorySource::new())
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+
+	loader.save("config.toml", &config()).await.unwrap();
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value, config());
+			assert_eq!(info.format, "toml");
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	loader.save("other.json", &config()).await.unwrap();
+	match loader.load_file::<TestConfig>("other.json").await {
+		LoadResult::Ok { value, .. } => assert_eq!(value, config()),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_save_errors() {
+	let loader = DynLoader::builder()
+		.writable_source(MemorySource::new())
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+	match loader.save("config.ini", &config()).await {
+		Err(FmtError::SerializeError(_)) => {}
+		other => panic!("Expected SerializeError, got {:?}", other),
+	}
+
+	let loader = DynLoader::builder()
+		.source(MemorySource::new())
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+	match loader.save("config.json", &config()).await {
+		Err(FmtError::Custom(_)) => {}
+		other => panic!("Expected Custom error, got {:?}", other),
+	}
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn test_save_file_source() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let loader = DynLoader::builder()
+		.writable_source(fmtstruct::FileSource::new(tmp_dir.path()))
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+
+	loader.save("config.json", &config()).await.unwrap();
+	let source = fmtstruct::FileSource::new(tmp_dir.path());
+	let bytes = source.read("config.json").await.unwrap();
+	assert!(String::from_utf8(bytes).unwrap().contains("\"port\": 8080"));
+}
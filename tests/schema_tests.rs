@@ -0,0 +1,160 @@
+/* tests/schema_tests.rs */
+
+#![cfg(feature = "schema")]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::schema::{self, JsonSchema};
+use fmtstruct::{DynLoader, Format, LoadResult, MemorySource, PreProcess, Source, Value};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct Server {
+	#[cfg_attr(feature = "validate", validate(length(min = 1, max = 253)))]
+	host: String,
+	#[cfg_attr(feature = "validate", validate(range(min = 1024, max = 65535)))]
+	port: u16,
+}
+
+/// Service configuration.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	/// Display name.
+	name: String,
+	#[cfg_attr(feature = "validate", validate(nested))]
+	server: Server,
+	#[cfg(feature = "regex")]
+	#[cfg_attr(feature = "validate", validate(regex(path = *RE_EMAIL)))]
+	email: String,
+}
+
+impl PreProcess for TestConfig {}
+
+#[cfg(feature = "regex")]
+static RE_EMAIL: std::sync::LazyLock<regex::Regex> =
+	std::sync::LazyLock::new(|| regex::Regex::new(r"^\w+@\w+\.\w+$").unwrap());
+
+fn schema_value() -> Value {
+	let bytes = schema::to_json::<TestConfig>().unwrap();
+	AnyFormat::Json.parse_value(&bytes).unwrap()
+}
+
+#[test]
+fn test_schema_generation() {
+	let schema = schema_value();
+	assert_eq!(
+		schema.get_path("description"),
+		Some(&Value::from("Service configuration."))
+	);
+	assert_eq!(
+		schema.get_path("properties.name.description"),
+		Some(&Value::from("Display name."))
+	);
+	assert_eq!(
+		schema.get_path("properties.$schema.type"),
+		Some(&Value::from("string"))
+	);
+
+	#[cfg(feature = "validate")]
+	{
+		let server = schema.get_path("$defs.Server.properties").unwrap();
+		assert_eq!(server.get_path("host.minLength"), Some(&Value::Integer(1)));
+		assert_eq!(
+			server.get_path("host.maxLength"),
+			Some(&Value::Integer(253))
+		);
+		assert_eq!(server.get_path("port.minimum"), Some(&Value::Integer(1024)));
+		assert_eq!(
+			server.get_path("port.maximum"),
+			Some(&Value::Integer(65535))
+		);
+	}
+
+	#[cfg(feature = "regex")]
+	assert_eq!(
+		schema.get_path("properties.email.pattern"),
+		Some(&Value::from(r"^\w+@\w+\.\w+$"))
+	);
+}
+
+#[test]
+fn test_schema_key() {
+	assert_eq!(schema::schema_key("config.toml"), "config.schema.json");
+	assert_eq!(
+		schema::schema_key("conf.d/app.prod.yaml"),
+		"conf.d/app.schema.json"
+	);
+	assert_eq!(schema::schema_key("config"), "config.schema.json");
+}
+
+#[tokio::test]
+async fn test_save_schema_and_reference() {
+	let source = Arc::new(MemorySource::new());
+	let loader = DynLoader::builder()
+		.writable_source(SharedSource(source.clone()))
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+
+	let key = loader
+		.save_schema::<TestConfig>("config.json")
+		.await
+		.unwrap();
+	assert_eq!(key, "config.schema.json");
+	assert!(source.exists("config.schema.json").await);
+
+	let config = TestConfig {
+		name: "app".to_string(),
+		server: Server {
+			host: "localhost".to_string(),
+			port: 8080,
+		},
+		#[cfg(feature = "regex")]
+		email: "ops@example.com".to_string(),
+	};
+	let document = schema::embed_schema(&config, "./config.schema.json").unwrap();
+	loader.save("config.json", &document).await.unwrap();
+
+	let written = String::from_utf8(source.read("config.json").await.unwrap()).unwrap();
+	assert!(
+		written.starts_with("{\n  \"$schema\": \"./config.schema.json\","),
+		"{}",
+		written
+	);
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, .. } => assert_eq!(value.server.port, 8080),
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+
+	assert!(schema::embed_schema(&[1, 2], "schema.json").is_err());
+}
+
+/// Shares a `MemorySource` with the test so it can inspect writes.
+struct SharedSource(Arc<MemorySource>);
+
+#[async_trait::async_trait]
+impl Source for SharedSource {
+	async fn read(&self, key: &str) -> Result<Vec<u8>, fmtstruct::FmtError> {
+		self.0.read(key).await
+	}
+
+	async fn exists(&self, key: &str) -> bool {
+		self.0.exists(key).await
+	}
+}
+
+#[async_trait::async_trait]
+impl fmtstruct::WritableSource for SharedSource {
+	async fn write(&self, key: &str, data: &[u8]) -> Result<(), fmtstruct::FmtError> {
+		self.0.write(key, data).await
+	}
+
+	async fn remove(&self, key: &str) -> Result<(), fmtstruct::FmtError> {
+		self.0.remove(key).await
+	}
+}
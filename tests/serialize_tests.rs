@@ -0,0 +1,90 @@
+/* tests/serialize_tests.rs */
+
+#![cfg(any(
+	feature = "json",
+	feature = "toml",
+	feature = "yaml",
+	feature = "postcard"
+))]
+
+use fmtstruct::Format;
+use fmtstruct::format::{AnyFormat, Style};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Server {
+	host: String,
+	port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TestConfig {
+	name: String,
+	tags: Vec<String>,
+	server: Server,
+}
+
+fn config() -> TestConfig {
+	TestConfig {
+		name: "app".to_string(),
+		tags: vec!["a".to_string(), "b".to_string()],
+		server: Server {
+			host: "localhost".to_string(),
+			port: 8080,
+		},
+	}
+}
+
+fn roundtrip(format: AnyFormat) {
+	for style in [Style::Compact, Style::Pretty] {
+		let bytes = format.serialize(&config(), style).unwrap();
+		let parsed: TestConfig = format.parse(&bytes).unwrap();
+		assert_eq!(parsed, config(), "{:?} {:?}", format, style);
+	}
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_serialize_json() {
+	roundtrip(AnyFormat::Json);
+
+	let compact = AnyFormat::Json
+		.serialize(&config(), Style::Compact)
+		.unwrap();
+	assert_eq!(
+		String::from_utf8(compact).unwrap(),
+		r#"{"name":"app","tags":["a","b"],"server":{"host":"localhost","port":8080}}"#
+	);
+	let pretty = AnyFormat::Json.serialize(&config(), Style::Pretty).unwrap();
+	assert!(
+		String::from_utf8(pretty)
+			.unwrap()
+			.contains("\n  \"name\": \"app\"")
+	);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_serialize_toml() {
+	roundtrip(AnyFormat::Toml);
+
+	let pretty = AnyFormat::Toml.serialize(&config(), Style::Pretty).unwrap();
+	let pretty = String::from_utf8(pretty).unwrap();
+	assert!(
+		pretty.contains("[server]\nhost = \"localhost\""),
+		"{}",
+		pretty
+	);
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_serialize_yaml() {
+	roundtrip(AnyFormat::Yaml);
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn test_serialize_postcard() {
+	roundtrip(AnyFormat::Postcard);
+}
@@ -58,3 +58,117 @@ async fn test_file_source_sandbox() {
 		_ => panic!("Expected SandboxViolation, got {:?}", result),
 	}
 }
+
+#[cfg(feature = "std")]
+#[tokio::test]
+async fn test_memory_source_write() {
+	use fmtstruct::WritableSource;
+
+	let source = MemorySource::new();
+	source.write("key", b"one").await.unwrap();
+	source.write("key", b"two").await.unwrap();
+	assert_eq!(source.read("key").await.unwrap(), b"two");
+
+	source.remove("key").await.unwrap();
+	assert!(!source.exists("key").await);
+	assert!(matches!(
+		source.remove("key").await,
+		Err(FmtError::NotFound)
+	));
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn test_file_source_write() {
+	use fmtstruct::WritableSource;
+
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let source = FileSource::new(tmp_dir.path());
+	let path = tmp_dir.path().join("config.toml");
+
+	source.write("config.toml", b"a = 1\n").await.unwrap();
+	assert_eq!(std::fs::read(&path).unwrap(), b"a = 1\n");
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+	}
+
+	source.write("config.toml", b"a = 2\n").await.unwrap();
+	assert_eq!(source.read("config.toml").await.unwrap(), b"a = 2\n");
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o600);
+	}
+
+	// Concurrent writers take turns on the lock
+	let source = std::sync::Arc::new(source);
+	let writes: Vec<_> = (0..8)
+		.map(|i| {
+			let source = source.clone();
+			tokio::spawn(async move {
+				source
+					.write("config.toml", format!("a = {}\n", i).as_bytes())
+					.await
+			})
+		})
+		.collect();
+	for write in writes {
+		write.await.unwrap().unwrap();
+	}
+
+	// No temporary files, nor on Unix a lock file, are left behind
+	let files: Vec<_> = std::fs::read_dir(tmp_dir.path())
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect();
+	#[cfg(unix)]
+	assert_eq!(files, ["config.toml"]);
+	assert!(
+		!files.iter().any(|name| name.ends_with(".tmp")),
+		"{:?}",
+		files
+	);
+
+	source.remove("config.toml").await.unwrap();
+	assert!(!path.exists());
+	assert!(matches!(
+		source.remove("config.toml").await,
+		Err(FmtError::NotFound)
+	));
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn test_file_source_write_sandbox() {
+	use fmtstruct::WritableSource;
+
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let root = tmp_dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	let source = FileSource::new(&root);
+
+	for key in ["../escape.toml", "/tmp/escape.toml"] {
+		match source.write(key, b"x").await {
+			Err(FmtError::SandboxViolation) => {}
+			other => panic!("Expected SandboxViolation for {}, got {:?}", key, other),
+		}
+	}
+	assert!(!tmp_dir.path().join("escape.toml").exists());
+
+	#[cfg(unix)]
+	{
+		let outside = tmp_dir.path().join("outside.toml");
+		std::fs::write(&outside, "x").unwrap();
+		std::os::unix::fs::symlink(&outside, root.join("link.toml")).unwrap();
+		match source.write("link.toml", b"y").await {
+			Err(FmtError::SandboxViolation) => {}
+			other => panic!("Expected SandboxViolation, got {:?}", other),
+		}
+		assert_eq!(std::fs::read(&outside).unwrap(), b"x");
+	}
+}
@@ -0,0 +1,177 @@
+/* tests/unknown_key_tests.rs */
+
+#![cfg(all(feature = "json", feature = "toml", feature = "std"))]
+
+use fmtstruct::format::{AnyFormat, Toml};
+use fmtstruct::{
+	DynLoader, FmtError, Layer, LayeredLoader, LoadResult, MemorySource, PreProcess, StaticLoader,
+	UnknownKey,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+	host: String,
+	#[serde(alias = "listen")]
+	port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+enum Backend {
+	Redis { url: String },
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	name: String,
+	servers: Vec<Server>,
+	#[serde(default)]
+	labels: BTreeMap<String, String>,
+	backend: Option<Backend>,
+}
+
+impl PreProcess for TestConfig {}
+
+// JSON, since the `toml` deserializer itself rejects unknown keys of enum
+// struct variants.
+const CONFIG: &str = r#"{
+	"$schema": "./config.schema.json",
+	"name": "app",
+	"verbose": true,
+	"labels": { "team": "core" },
+	"servers": [
+		{ "host": "a", "port": 80 },
+		{ "host": "b", "listen": 81, "prot": 8080 }
+	],
+	"backend": { "Redis": { "url": "redis://localhost", "urls": [] } }
+}"#;
+
+fn key(path: &str, suggestion: Option<&'static str>, file: &str) -> UnknownKey {
+	UnknownKey {
+		path: path.to_string(),
+		suggestion,
+		file: Some(file.to_string()),
+		line: None,
+		column: None,
+		span: None,
+		#[cfg(feature = "diagnostics")]
+		source: None,
+	}
+}
+
+#[tokio::test]
+async fn test_unknown_keys_are_reported() {
+	let mut source = MemorySource::new();
+	source.insert("config.json", CONFIG.as_bytes().to_vec());
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Ok { value, info } => {
+			assert_eq!(value.name, "app");
+			assert_eq!(value.servers[1].host, "b");
+			assert_eq!(value.servers[1].port, 81);
+			assert_eq!(value.labels["team"], "core");
+			match value.backend {
+				Some(Backend::Redis { url }) => assert_eq!(url, "redis://localhost"),
+				other => panic!("Expected Redis backend, got {:?}", other),
+			}
+			assert_eq!(
+				info.unknown_keys,
+				[
+					key("verbose", None, "config.json"),
+					key("backend.Redis.urls", Some("url"), "config.json"),
+					key("servers[1].prot", Some("port"), "config.json"),
+				]
+			);
+			assert_eq!(
+				info.unknown_keys[2].to_string(),
+				"servers[1].prot (did you mean `port`?)"
+			);
+		}
+		other => panic!("Expected Ok, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_deny_unknown_keys() {
+	let mut source = MemorySource::new();
+	source.insert("config.json", CONFIG.as_bytes().to_vec());
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Json)
+		.deny_unknown_keys()
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>("config").await {
+		LoadResult::Invalid(FmtError::UnknownKeys(keys)) => {
+			assert_eq!(keys.len(), 3);
+			assert_eq!(
+				FmtError::UnknownKeys(keys).to_string(),
+				"unknown keys: verbose; backend.Redis.urls (did you mean `url`?); \
+				 servers[1].prot (did you mean `port`?)"
+			);
+		}
+		other => panic!("Expected Invalid(UnknownKeys), got {:?}", other),
+	}
+
+	let mut source = MemorySource::new();
+	source.insert(
+		"config.toml",
+		b"name = \"app\"\nnmae = \"app\"\nservers = []\n".to_vec(),
+	);
+	let loader = StaticLoader::new(source, Toml).deny_unknown_keys();
+	match loader.load::<TestConfig>("config.toml").await {
+		LoadResult::Invalid(FmtError::UnknownKeys(keys)) => {
+			assert_eq!(keys, [key("nmae", Some("name"), "config.toml")]);
+		}
+		other => panic!("Expected Invalid(UnknownKeys), got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn test_unknown_keys_across_layers() {
+	let defaults = {
+		let mut source = MemorySource::new();
+		source.insert(
+			"defaults.json",
+			br#"{ "name": "app", "servers": [] }"#.to_vec(),
+		);
+		source
+	};
+	let user = {
+		let mut source = MemorySource::new();
+		source.insert("user.toml", b"nam = \"mine\"\n".to_vec());
+		source
+	};
+	let loader = LayeredLoader::builder()
+		.layer(Layer::new(defaults, "defaults.json", AnyFormat::Json))
+		.layer(Layer::new(user, "user.toml", AnyFormat::Toml))
+		.deny_unknown_keys()
+		.build()
+		.unwrap();
+
+	match loader.load::<TestConfig>().await {
+		LoadResult::Invalid(FmtError::UnknownKeys(keys)) => {
+			assert_eq!(keys.len(), 1);
+			assert_eq!(
+				(keys[0].path.as_str(), keys[0].suggestion),
+				("nam", Some("name"))
+			);
+			// TOML reports where the key itself is
+			assert_eq!(keys[0].file.as_deref(), Some("user.toml"));
+			assert_eq!((keys[0].line, keys[0].column), (Some(1), Some(1)));
+			assert_eq!(keys[0].span, Some(0..3));
+		}
+		other => panic!("Expected Invalid(UnknownKeys), got {:?}", other),
+	}
+}
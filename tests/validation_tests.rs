@@ -3,8 +3,10 @@
 #![cfg(all(feature = "json", feature = "validate"))]
 
 use async_trait::async_trait;
-use fmtstruct::format::AnyFormat;
-use fmtstruct::{DynLoader, FmtError, LoadResult, PreProcess, Source, ValidateConfig};
+use fmtstruct::format::{AnyFormat, Json};
+use fmtstruct::{
+	DynLoader, FmtError, LoadResult, PreProcess, Source, StaticLoader, ValidateConfig,
+};
 use serde::Deserialize;
 use std::collections::HashMap;
 use validator::Validate;
@@ -71,17 +73,55 @@ async fn test_validation_failure() {
 
 	let loader = DynLoader::new(Box::new(source), vec![AnyFormat::Json]);
 
+	let result: LoadResult<TestConfig> = loader.load("config").await;
+	match result {
+		#[cfg(feature = "std")]
+		LoadResult::Invalid(FmtError::Validation(errors)) => {
+			assert!(errors.field_errors().contains_key("name"));
+		}
+		#[cfg(not(feature = "std"))]
+		LoadResult::Invalid(FmtError::Validation(..)) => {}
+		_ => panic!("Expected Invalid(Validation), got {:?}", result),
+	}
+}
+
+#[tokio::test]
+async fn test_skip_validation() {
+	let mut data = HashMap::new();
+	let content = if cfg!(feature = "regex") {
+		r#"{ "name": "al", "email": "alice@example.com" }"#
+	} else {
+		r#"{ "name": "al" }"#
+	};
+	data.insert("config.json".to_string(), content.as_bytes().to_vec());
+
+	let loader = DynLoader::builder()
+		.source(MockSource { data: data.clone() })
+		.format(AnyFormat::Json)
+		.skip_validation()
+		.build()
+		.unwrap();
 	let result: LoadResult<TestConfig> = loader.load("config").await;
 	match result {
 		LoadResult::Ok { value, .. } => {
-			if let Err(FmtError::Validation(_)) = value.validate_config() {
-				// success
-			} else {
-				panic!("Expected Validation error, got Ok");
-			}
+			assert_eq!(value.name, "al");
+			assert!(matches!(
+				value.validate_config(),
+				Err(FmtError::Validation(..))
+			));
 		}
 		_ => panic!("Expected Ok (with invalid data), got {:?}", result),
 	}
+
+	let loader = StaticLoader::new(MockSource { data }, Json);
+	let result: LoadResult<TestConfig> = loader.load("config.json").await;
+	assert!(
+		matches!(result, LoadResult::Invalid(FmtError::Validation(..))),
+		"Expected Invalid(Validation), got {:?}",
+		result
+	);
+	let result: LoadResult<TestConfig> = loader.skip_validation().load("config.json").await;
+	assert!(matches!(result, LoadResult::Ok { .. }));
 }
 
 #[cfg(feature = "regex")]
@@ -98,13 +138,10 @@ async fn test_regex_validation_failure() {
 
 	let result: LoadResult<TestConfig> = loader.load("config").await;
 	match result {
-		LoadResult::Ok { value, .. } => {
-			if let Err(FmtError::Validation(_)) = value.validate_config() {
-				// success
-			} else {
-				panic!("Expected Validation error for regex, got Ok");
-			}
+		#[cfg(feature = "std")]
+		LoadResult::Invalid(FmtError::Validation(errors)) => {
+			assert!(errors.field_errors().contains_key("email"));
 		}
-		_ => panic!("Expected Ok (with invalid data), got {:?}", result),
+		_ => panic!("Expected Invalid(Validation) for regex, got {:?}", result),
 	}
 }
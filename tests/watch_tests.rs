@@ -0,0 +1,113 @@
+/* tests/watch_tests.rs */
+
+#![cfg(all(feature = "watch", feature = "json", feature = "toml"))]
+
+use fmtstruct::format::AnyFormat;
+use fmtstruct::{ConfigHandle, DynLoader, FileSource, FileWatcher, FmtError, PreProcess};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "validate")]
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "validate", derive(Validate))]
+struct TestConfig {
+	port: u16,
+}
+
+impl PreProcess for TestConfig {}
+
+fn loader(root: &Path) -> DynLoader {
+	DynLoader::builder()
+		.source(FileSource::new(root))
+		.format(AnyFormat::Toml)
+		.format(AnyFormat::Json)
+		.build()
+		.unwrap()
+}
+
+async fn next(handle: &mut ConfigHandle<TestConfig>) -> u16 {
+	tokio::time::timeout(Duration::from_secs(5), handle.changed())
+		.await
+		.expect("timed out waiting for reload");
+	handle.get().port
+}
+
+#[tokio::test]
+async fn test_watch_modify() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let path = tmp_dir.path().join("config.toml");
+	std::fs::write(&path, "port = 1\n").unwrap();
+
+	let mut handle = FileWatcher::new(loader(tmp_dir.path()), "config")
+		.debounce(Duration::from_millis(20))
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+	assert_eq!(handle.get().port, 1);
+
+	std::fs::write(&path, "port = 2\n").unwrap();
+	assert_eq!(next(&mut handle).await, 2);
+}
+
+#[tokio::test]
+async fn test_watch_rename_save() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let path = tmp_dir.path().join("config.toml");
+	std::fs::write(&path, "port = 1\n").unwrap();
+
+	let mut handle = FileWatcher::new(loader(tmp_dir.path()), "config")
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+	let reader = handle.clone();
+
+	let tmp = tmp_dir.path().join(".config.toml.swp");
+	std::fs::write(&tmp, "port = 3\n").unwrap();
+	std::fs::rename(&tmp, &path).unwrap();
+	assert_eq!(next(&mut handle).await, 3);
+	assert_eq!(reader.get().port, 3);
+}
+
+#[tokio::test]
+async fn test_watch_invalid_keeps_previous() {
+	let tmp_dir = tempfile::tempdir().unwrap();
+	let path = tmp_dir.path().join("config.json");
+	std::fs::write(&path, r#"{ "port": 1 }"#).unwrap();
+
+	let mut handle = FileWatcher::new(loader(tmp_dir.path()), "config")
+		.debounce(Duration::from_millis(20))
+		.start::<TestConfig>()
+		.await
+		.unwrap();
+
+	std::fs::write(&path, r#"{ "port": "#).unwrap();
+	tokio::time::sleep(Duration::from_millis(200)).await;
+	assert_eq!(handle.get().port, 1);
+	assert!(handle.error().is_some());
+
+	std::fs::write(&path, r#"{ "port": 4 }"#).unwrap();
+	assert_eq!(next(&mut handle).await, 4);
+	assert!(handle.error().is_none());
+}
+
+#[tokio::test]
+async fn test_watch_requires_local_files() {
+	let mut source = fmtstruct::MemorySource::new();
+	source.insert("config.toml", b"port = 1\n".to_vec());
+	let loader = DynLoader::builder()
+		.source(source)
+		.format(AnyFormat::Toml)
+		.build()
+		.unwrap();
+
+	match FileWatcher::new(loader, "config")
+		.start::<TestConfig>()
+		.await
+	{
+		Err(FmtError::Custom(_)) => {}
+		other => panic!("Expected Custom error, got {:?}", other),
+	}
+}